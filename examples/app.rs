@@ -8,6 +8,7 @@ USAGE:
 
 FLAGS:
   -h, --help            Prints help information
+  -V, --version         Prints version information
 
 OPTIONS:
   --number NUMBER       Sets a number
@@ -43,9 +44,9 @@ fn main() {
 fn parse_args() -> Result<AppArgs, pico_args::Error> {
     let mut pargs = pico_args::Arguments::from_env();
 
-    // Help has a higher priority and should be handled separately.
-    if pargs.contains(["-h", "--help"]) {
-        print!("{}", HELP);
+    // Help and version have a higher priority and should be handled separately.
+    let version = concat!("app ", env!("CARGO_PKG_VERSION"), "\n");
+    if pargs.handle_common_flags(HELP, version).is_some() {
         std::process::exit(0);
     }
 
@@ -0,0 +1,31 @@
+// Logs every parsing step, so an invocation can be reconstructed from logs.
+//
+// pico-args has no dependencies, so the observer writes to stderr.
+// In an application, replace `eprintln!` with `log::trace!` or `tracing::trace!`.
+//
+// Run with: cargo run --example trace -- -v --width 10 file.txt
+
+use pico_args::{Arguments, Event};
+
+fn trace(event: Event) {
+    match event {
+        Event::Lookup(keys) => eprintln!("TRACE lookup {}", keys),
+        Event::Flag { key, position } => eprintln!("TRACE flag {} at {}", key, position),
+        Event::Value { key, value, position } => {
+            eprintln!("TRACE value {} = {:?} at {}", key, value, position)
+        }
+        Event::Free { value, position } => eprintln!("TRACE free {:?} at {}", value, position),
+        Event::Error(e) => eprintln!("TRACE error {}", e),
+        _ => {}
+    }
+}
+
+fn main() {
+    let mut args = Arguments::from_env();
+    args.set_observer(trace);
+
+    let verbose = args.contains(["-v", "--verbose"]);
+    let width: Option<u32> = args.opt_value_from_str("--width").unwrap_or(None);
+    let input: Option<String> = args.opt_free_from_str().unwrap_or(None);
+    println!("verbose: {}, width: {:?}, input: {:?}", verbose, width, input);
+}
@@ -0,0 +1,150 @@
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Windows limits the whole command line to 32767 UTF-16 code units,
+// including the terminating NUL.
+#[cfg(windows)]
+const MAX_CMDLINE_LEN: usize = 32767;
+
+// `ARG_MAX` includes the environment, the strings' NUL terminators
+// and the pointers to them. The values below are the usual defaults.
+#[cfg(all(unix, target_os = "linux"))]
+const ARG_MAX: usize = 2 * 1024 * 1024;
+#[cfg(all(unix, any(target_os = "macos", target_os = "ios")))]
+const ARG_MAX: usize = 1024 * 1024;
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos", target_os = "ios"))))]
+const ARG_MAX: usize = 256 * 1024;
+
+// Linux also limits the length of a single argument.
+#[cfg(all(unix, target_os = "linux"))]
+const MAX_ARG_STRLEN: usize = 128 * 1024;
+
+/// Checks that a command line would be accepted by the OS.
+///
+/// `args` must contain the full command line, including the executable path.
+///
+/// The check is conservative: on Windows it accounts for the quoting required
+/// by `CommandLineToArgvW`, and on Unix for the current environment,
+/// which shares the `ARG_MAX` budget with the arguments.
+///
+/// When it returns `false`, the arguments can be passed via a file instead.
+/// See [`write_argfile`].
+///
+/// [`write_argfile`]: fn.write_argfile.html
+pub fn fits_in_os_limits(args: &[OsString]) -> bool {
+    fits_in_os_limits_impl(args)
+}
+
+#[cfg(windows)]
+fn fits_in_os_limits_impl(args: &[OsString]) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+
+    let mut len = 0;
+    for arg in args {
+        let mut needs_quotes = arg.is_empty();
+        let mut backslashes = 0;
+        for c in arg.encode_wide() {
+            match c {
+                0x5C => backslashes += 1, // '\'
+                0x22 => { len += backslashes + 1; backslashes = 0; } // '"'
+                0x20 | 0x09 => { needs_quotes = true; backslashes = 0; }
+                _ => backslashes = 0,
+            }
+            len += 1;
+        }
+
+        if needs_quotes {
+            // Trailing backslashes must be doubled before the closing quote.
+            len += backslashes + 2;
+        }
+
+        len += 1; // A separator or the terminating NUL.
+    }
+
+    len <= MAX_CMDLINE_LEN
+}
+
+#[cfg(unix)]
+fn fits_in_os_limits_impl(args: &[OsString]) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let ptr_size = std::mem::size_of::<usize>();
+
+    #[cfg(target_os = "linux")]
+    {
+        if args.iter().any(|arg| arg.as_bytes().len() + 1 > MAX_ARG_STRLEN) {
+            return false;
+        }
+    }
+
+    let mut len = 0;
+    for arg in args {
+        len += arg.as_bytes().len() + 1 + ptr_size;
+    }
+
+    for (key, value) in std::env::vars_os() {
+        // `KEY=VALUE\0`
+        len += key.as_bytes().len() + value.as_bytes().len() + 2 + ptr_size;
+    }
+
+    // The terminating NULL pointers of `argv` and `envp`.
+    len += 2 * ptr_size;
+
+    len <= ARG_MAX
+}
+
+#[cfg(not(any(unix, windows)))]
+fn fits_in_os_limits_impl(_: &[OsString]) -> bool {
+    true
+}
+
+/// Writes arguments into a response file.
+///
+/// Each argument is written on a separate line, surrounded by double quotes,
+/// with `"` and `\` escaped using a backslash. This format is understood
+/// by GCC, Clang, `javac` and many other tools.
+///
+/// Returns the `@path` argument that should be passed instead.
+///
+/// The executable path should not be included.
+///
+/// # Errors
+///
+/// - When the file cannot be written.
+/// - When an argument is not a valid UTF-8 string on non-Unix platforms.
+pub fn write_argfile<P: AsRef<Path>>(path: P, args: &[OsString]) -> io::Result<OsString> {
+    let path = path.as_ref();
+
+    let mut data = Vec::new();
+    for arg in args {
+        data.push(b'"');
+        for &c in arg_bytes(arg)? {
+            if c == b'"' || c == b'\\' {
+                data.push(b'\\');
+            }
+            data.push(c);
+        }
+        data.extend_from_slice(b"\"\n");
+    }
+
+    std::fs::File::create(path)?.write_all(&data)?;
+
+    let mut arg = OsString::from("@");
+    arg.push(path);
+    Ok(arg)
+}
+
+#[cfg(unix)]
+fn arg_bytes(arg: &OsStr) -> io::Result<&[u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(arg.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn arg_bytes(arg: &OsStr) -> io::Result<&[u8]> {
+    match arg.to_str() {
+        Some(s) => Ok(s.as_bytes()),
+        None => Err(io::Error::new(io::ErrorKind::InvalidData, "argument is not a UTF-8 string")),
+    }
+}
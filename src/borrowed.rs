@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{arg_to_str, error_to_string, os_to_str, Arg, ArgStr, Error, Keys};
+
+/// A borrowed arguments parser.
+///
+/// A reduced version of [`Arguments`], that works on top of a slice of arguments
+/// and marks them as used instead of removing them, so the arguments are never cloned.
+/// Useful for libraries that receive arguments by reference.
+///
+/// Supports flags, `--key value` pairs and free-standing arguments.
+/// `--key=value` pairs are supported only with the `eq-separator` feature.
+/// Combined flags and the `-Kvalue` syntax are not supported.
+///
+/// [`Arguments`]: struct.Arguments.html
+#[derive(Clone, Debug)]
+pub struct ArgumentsRef<'a> {
+    args: &'a [Arg],
+    used: Vec<bool>,
+}
+
+impl<'a> ArgumentsRef<'a> {
+    /// Creates a parser from a slice of arguments.
+    ///
+    /// The executable path **must** be skipped.
+    pub fn new(args: &'a [Arg]) -> Self {
+        ArgumentsRef {
+            args,
+            used: vec![false; args.len()],
+        }
+    }
+
+    /// Checks that arguments contain a specified flag.
+    ///
+    /// The same as [`Arguments::contains`].
+    ///
+    /// [`Arguments::contains`]: struct.Arguments.html#method.contains
+    pub fn contains<A: Into<Keys>>(&mut self, keys: A) -> bool {
+        self.contains_impl(keys.into())
+    }
+
+    #[inline(never)]
+    fn contains_impl(&mut self, keys: Keys) -> bool {
+        match self.index_of(keys) {
+            Some((idx, _)) => {
+                self.used[idx] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an optional key-value pair value without copying it.
+    ///
+    /// The value is borrowed from the original arguments, unless it was quoted.
+    ///
+    /// # Errors
+    ///
+    /// - When key or value is not a UTF-8 string.
+    /// - When key is not followed by a value.
+    pub fn opt_value_str<A: Into<Keys>>(&mut self, keys: A) -> Result<Option<Cow<'a, str>>, Error> {
+        self.opt_value_str_impl(keys.into()).map(|v| v.map(|(_, value)| value))
+    }
+
+    #[inline(never)]
+    fn opt_value_str_impl(&mut self, keys: Keys) -> Result<Option<(&'static str, Cow<'a, str>)>, Error> {
+        let args = self.args;
+
+        if let Some((idx, key)) = self.index_of(keys) {
+            let value_idx = match self.next_unused(idx + 1) {
+                Some(v) => v,
+                None => return Err(Error::OptionWithoutAValue(key)),
+            };
+
+            let value = os_to_str(&args[value_idx])?;
+            self.used[idx] = true;
+            self.used[value_idx] = true;
+            return Ok(Some((key, Cow::Borrowed(value))));
+        }
+
+        #[cfg(feature = "eq-separator")]
+        {
+            for key in &keys.0 {
+                if key.is_empty() {
+                    continue;
+                }
+
+                let idx = match self.unused().find(|&i| crate::starts_with_plus_eq(&args[i], key)) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                let value = os_to_str(&args[idx])?;
+                let value = match crate::parse_eq_value(&value[key.len() + 1..]) {
+                    Some(v) => v,
+                    None => return Err(Error::OptionWithoutAValue(key)),
+                };
+
+                self.used[idx] = true;
+                return Ok(Some((key, value)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Parses a key-value pair using `FromStr` trait.
+    ///
+    /// The same as [`Arguments::value_from_str`].
+    ///
+    /// [`Arguments::value_from_str`]: struct.Arguments.html#method.value_from_str
+    pub fn value_from_str<A, T>(&mut self, keys: A) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let keys = keys.into();
+        match self.opt_value_from_str(keys) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => Err(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses an optional key-value pair using `FromStr` trait.
+    ///
+    /// The same as [`Arguments::opt_value_from_str`].
+    /// Unlike the owning parser, the value is marked as used even when parsing failed.
+    ///
+    /// [`Arguments::opt_value_from_str`]: struct.Arguments.html#method.opt_value_from_str
+    pub fn opt_value_from_str<A, T>(&mut self, keys: A) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let (key, value) = match self.opt_value_str_impl(keys.into())? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        match T::from_str(&value) {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => Err(Error::OptionParsingFailed {
+                key,
+                value: value.into_owned(),
+                cause: error_to_string(e),
+            }),
+        }
+    }
+
+    /// Returns an optional free-standing argument without copying it.
+    ///
+    /// # Errors
+    ///
+    /// - When argument is not a UTF-8 string.
+    pub fn opt_free_str(&mut self) -> Result<Option<&'a str>, Error> {
+        let args = self.args;
+        match self.next_unused(0) {
+            Some(idx) => {
+                let value = os_to_str(&args[idx])?;
+                self.used[idx] = true;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a free-standing argument using `FromStr` trait.
+    ///
+    /// The same as [`Arguments::free_from_str`].
+    ///
+    /// [`Arguments::free_from_str`]: struct.Arguments.html#method.free_from_str
+    pub fn free_from_str<T>(&mut self) -> Result<T, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.opt_free_from_str()?.ok_or(Error::MissingArgument)
+    }
+
+    /// Parses an optional free-standing argument using `FromStr` trait.
+    ///
+    /// The same as [`Arguments::opt_free_from_str`].
+    ///
+    /// [`Arguments::opt_free_from_str`]: struct.Arguments.html#method.opt_free_from_str
+    pub fn opt_free_from_str<T>(&mut self) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let value = match self.opt_free_str()? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        match T::from_str(value) {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => Err(Error::Utf8ArgumentParsingFailed {
+                value: value.to_string(),
+                cause: error_to_string(e),
+            }),
+        }
+    }
+
+    /// Returns a list of remaining arguments.
+    pub fn finish(self) -> Vec<&'a ArgStr> {
+        let args = self.args;
+        self.unused().map(|i| args[i].as_ref()).collect()
+    }
+
+    fn unused<'b>(&'b self) -> impl Iterator<Item = usize> + 'b {
+        self.used.iter().enumerate().filter(|(_, used)| !**used).map(|(i, _)| i)
+    }
+
+    fn next_unused(&self, start: usize) -> Option<usize> {
+        self.unused().find(|&i| i >= start)
+    }
+
+    #[inline(never)]
+    fn index_of(&self, keys: Keys) -> Option<(usize, &'static str)> {
+        for key in &keys.0 {
+            if !key.is_empty() {
+                if let Some(i) = self.unused().find(|&i| arg_to_str(&self.args[i]) == Some(*key)) {
+                    return Some((i, key));
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,376 @@
+/*!
+A minimal builder API on top of [`Arguments`].
+
+Drives the parsing and renders a help message and a man page from the same description.
+
+```
+use pico_args::builder::Cli;
+
+let cli = Cli::new("app")
+    .about("Does things")
+    .flag(["-h", "--help"], "Prints help information")
+    .opt("--width", "WIDTH", "Sets width")
+    .positional("INPUT", "Sets an input file");
+
+let mut args = pico_args::Arguments::from_iter(["--width", "10", "in.txt"].iter().cloned());
+let matches = cli.parse(&mut args).unwrap();
+assert!(!matches.contains("--help"));
+assert_eq!(matches.value_from_str::<u32>("--width").unwrap(), 10);
+assert_eq!(matches.positional("INPUT"), Some("in.txt"));
+```
+
+[`Arguments`]: ../struct.Arguments.html
+*/
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::help::Table;
+use crate::{error_to_string, Arguments, Error, Keys};
+
+#[derive(Clone, Debug)]
+enum Kind {
+    Flag,
+    Option(&'static str),
+    Positional,
+}
+
+#[derive(Clone, Debug)]
+struct Item {
+    keys: Keys,
+    name: &'static str,
+    kind: Kind,
+    help: &'static str,
+}
+
+impl Item {
+    fn is_positional(&self) -> bool {
+        matches!(self.kind, Kind::Positional)
+    }
+}
+
+/// A command line interface description.
+#[derive(Clone, Debug)]
+pub struct Cli {
+    name: &'static str,
+    about: &'static str,
+    items: Vec<Item>,
+}
+
+impl Cli {
+    /// Creates a new description for an application with a specified name.
+    pub fn new(name: &'static str) -> Self {
+        Cli { name, about: "", items: Vec::new() }
+    }
+
+    /// Sets a short application description, that will be printed before the usage.
+    pub fn about(mut self, text: &'static str) -> Self {
+        self.about = text;
+        self
+    }
+
+    /// Adds a flag.
+    pub fn flag<A: Into<Keys>>(mut self, keys: A, help: &'static str) -> Self {
+        self.items.push(Item { keys: keys.into(), name: "", kind: Kind::Flag, help });
+        self
+    }
+
+    /// Adds an option with a value.
+    ///
+    /// `placeholder` is used only in the help message, like `--width WIDTH`.
+    pub fn opt<A: Into<Keys>>(mut self, keys: A, placeholder: &'static str, help: &'static str) -> Self {
+        self.items.push(Item { keys: keys.into(), name: "", kind: Kind::Option(placeholder), help });
+        self
+    }
+
+    /// Adds a free-standing argument.
+    ///
+    /// Positional arguments are parsed in the order they were added.
+    pub fn positional(mut self, name: &'static str, help: &'static str) -> Self {
+        self.items.push(Item { keys: Keys(["", ""]), name, kind: Kind::Positional, help });
+        self
+    }
+
+    /// Renders a help message.
+    ///
+    /// Descriptions are aligned and wrapped using [`help::Table`].
+    ///
+    /// [`help::Table`]: ../help/struct.Table.html
+    pub fn help(&self) -> String {
+        let mut flags = Vec::new();
+        let mut options = Vec::new();
+        let mut positionals = Vec::new();
+        for item in &self.items {
+            match item.kind {
+                Kind::Flag => flags.push((keys_to_string(item.keys), "", item.help)),
+                Kind::Option(placeholder) => {
+                    options.push((keys_to_string(item.keys), placeholder, item.help));
+                }
+                Kind::Positional => positionals.push((format!("<{}>", item.name), "", item.help)),
+            }
+        }
+
+        let mut text = String::new();
+        if !self.about.is_empty() {
+            text.push_str(self.name);
+            text.push_str(" - ");
+            text.push_str(self.about);
+            text.push_str("\n\n");
+        }
+
+        text.push_str("USAGE:\n  ");
+        text.push_str(self.name);
+        if !flags.is_empty() || !options.is_empty() {
+            text.push_str(" [OPTIONS]");
+        }
+        for (name, _, _) in &positionals {
+            text.push(' ');
+            text.push_str(name);
+        }
+        text.push('\n');
+
+        let mut table = Table::new();
+        for (title, rows) in &[("FLAGS", &flags), ("OPTIONS", &options), ("ARGS", &positionals)] {
+            if rows.is_empty() {
+                continue;
+            }
+
+            table.section(title);
+            for (keys, placeholder, help) in rows.iter() {
+                table.row(keys, placeholder, help);
+            }
+        }
+
+        if !self.items.is_empty() {
+            text.push('\n');
+            text.push_str(&table.to_string());
+        }
+
+        text
+    }
+
+    /// Renders a man page in the roff format.
+    ///
+    /// Can be used by packaging scripts to generate `app.1` from the same description.
+    ///
+    /// ```
+    /// use pico_args::builder::Cli;
+    ///
+    /// let cli = Cli::new("app").flag(["-h", "--help"], "Prints help information");
+    /// assert!(cli.to_man().starts_with(".TH APP 1\n"));
+    /// ```
+    pub fn to_man(&self) -> String {
+        let mut text = String::new();
+        text.push_str(&format!(".TH {} 1\n", roff_escape(&self.name.to_uppercase())));
+
+        text.push_str(".SH NAME\n");
+        text.push_str(&roff_escape(self.name));
+        if !self.about.is_empty() {
+            text.push_str(" \\- ");
+            text.push_str(&roff_escape(self.about));
+        }
+        text.push('\n');
+
+        text.push_str(".SH SYNOPSIS\n.B ");
+        text.push_str(&roff_escape(self.name));
+        text.push('\n');
+        let mut synopsis = Vec::new();
+        if self.items.iter().any(|item| !item.is_positional()) {
+            synopsis.push("[OPTIONS]".to_string());
+        }
+        for item in self.items.iter().filter(|item| item.is_positional()) {
+            synopsis.push(format!("\\fI{}\\fR", roff_escape(item.name)));
+        }
+        if !synopsis.is_empty() {
+            text.push_str(&synopsis.join(" "));
+            text.push('\n');
+        }
+
+        let mut options = String::new();
+        let mut positionals = String::new();
+        for item in &self.items {
+            let (section, term) = match item.kind {
+                Kind::Flag => (&mut options, roff_keys(item.keys)),
+                Kind::Option(placeholder) => {
+                    let term = format!("{} \\fI{}\\fR", roff_keys(item.keys), roff_escape(placeholder));
+                    (&mut options, term)
+                }
+                Kind::Positional => {
+                    (&mut positionals, format!("\\fI{}\\fR", roff_escape(item.name)))
+                }
+            };
+
+            section.push_str(".TP\n");
+            section.push_str(&term);
+            section.push('\n');
+            section.push_str(&roff_escape(item.help));
+            section.push('\n');
+        }
+
+        for (title, section) in &[("OPTIONS", options), ("ARGUMENTS", positionals)] {
+            if !section.is_empty() {
+                text.push_str(".SH ");
+                text.push_str(title);
+                text.push('\n');
+                text.push_str(section);
+            }
+        }
+
+        text
+    }
+
+    /// Parses arguments according to the description.
+    ///
+    /// Options are parsed before flags to prevent ambiguities.
+    /// Parsed arguments are removed, so the remaining ones can be checked
+    /// via [`Arguments::finish`] afterwards.
+    ///
+    /// [`Arguments::finish`]: ../struct.Arguments.html#method.finish
+    pub fn parse(&self, args: &mut Arguments) -> Result<Matches, Error> {
+        let mut values = Vec::with_capacity(self.items.len());
+        for item in &self.items {
+            if let Kind::Option(_) = item.kind {
+                values.push((item, args.opt_value_from_str::<_, String>(item.keys)?));
+            }
+        }
+
+        for item in &self.items {
+            if let Kind::Flag = item.kind {
+                let value = if args.contains(item.keys) { Some(String::new()) } else { None };
+                values.push((item, value));
+            }
+        }
+
+        for item in &self.items {
+            if let Kind::Positional = item.kind {
+                values.push((item, args.opt_free_from_str::<String>()?));
+            }
+        }
+
+        Ok(Matches {
+            values: values.into_iter()
+                .map(|(item, value)| (item.keys, item.name, value))
+                .collect(),
+        })
+    }
+}
+
+fn has_key(keys: Keys, key: &str) -> bool {
+    keys.first() == key || keys.second() == key
+}
+
+fn keys_to_string(keys: Keys) -> String {
+    if keys.second().is_empty() {
+        keys.first().to_string()
+    } else {
+        format!("{}, {}", keys.first(), keys.second())
+    }
+}
+
+fn roff_keys(keys: Keys) -> String {
+    keys.iter()
+        .map(|key| format!("\\fB{}\\fR", roff_escape(key)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Escapes text, so it will not be treated as roff requests or escapes.
+fn roff_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\e"),
+            '-' => escaped.push_str("\\-"),
+            '.' | '\'' if i == 0 => escaped.push_str(if c == '.' { "\\&." } else { "\\&'" }),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Parsed arguments.
+#[derive(Clone, Debug)]
+pub struct Matches {
+    values: Vec<(Keys, &'static str, Option<String>)>,
+}
+
+impl Matches {
+    /// Checks that a flag or an option was present.
+    ///
+    /// Accepts any of the item keys.
+    pub fn contains(&self, key: &str) -> bool {
+        self.find(|keys, _| has_key(keys, key)).is_some()
+    }
+
+    /// Returns an option value.
+    ///
+    /// Accepts any of the option keys.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.find(|keys, _| has_key(keys, key))
+    }
+
+    /// Parses an option value using `FromStr` trait.
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When value parsing failed.
+    ///
+    /// # Panics
+    ///
+    /// - When the key is not described by the [`Cli`].
+    ///
+    /// [`Cli`]: struct.Cli.html
+    pub fn value_from_str<T>(&self, key: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        match self.opt_value_from_str(key)? {
+            Some(v) => Ok(v),
+            None => {
+                let keys = match self.values.iter().find(|(keys, _, _)| has_key(*keys, key)) {
+                    Some((keys, _, _)) => *keys,
+                    None => panic!("'{}' is not described by the Cli", key),
+                };
+                Err(Error::MissingOption(keys))
+            }
+        }
+    }
+
+    /// Parses an optional option value using `FromStr` trait.
+    ///
+    /// The same as [`value_from_str`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`value_from_str`]: struct.Matches.html#method.value_from_str
+    pub fn opt_value_from_str<T>(&self, key: &str) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let (keys, value) = match self.values.iter().find(|(keys, _, _)| has_key(*keys, key)) {
+            Some((keys, _, Some(value))) => (keys, value),
+            _ => return Ok(None),
+        };
+
+        match T::from_str(value) {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => Err(Error::OptionParsingFailed {
+                key: if keys.second().is_empty() { keys.first() } else { keys.second() },
+                value: value.clone(),
+                cause: error_to_string(e),
+            }),
+        }
+    }
+
+    /// Returns a free-standing argument by its name.
+    pub fn positional(&self, name: &str) -> Option<&str> {
+        self.find(|_, n| !n.is_empty() && n == name)
+    }
+
+    fn find<F: Fn(Keys, &str) -> bool>(&self, f: F) -> Option<&str> {
+        self.values.iter()
+            .find(|(keys, name, value)| value.is_some() && f(*keys, name))
+            .and_then(|(_, _, value)| value.as_ref().map(|s| s.as_str()))
+    }
+}
@@ -0,0 +1,100 @@
+// Splits a raw Windows command line using the `CommandLineToArgvW` rules.
+//
+// The first argument is the program name, which is parsed differently:
+// it ends at the first whitespace, unless quoted, and backslashes are taken verbatim.
+//
+// For the rest:
+// - arguments are separated by spaces or tabs, unless quoted
+// - `2n` backslashes followed by `"` produce `n` backslashes and toggle quoting
+// - `2n + 1` backslashes followed by `"` produce `n` backslashes and a literal `"`
+// - backslashes not followed by `"` are taken verbatim
+// - `""` inside a quoted argument produces a literal `"`
+pub(crate) fn split(line: &[u16]) -> Vec<Vec<u16>> {
+    const SPACE: u16 = b' ' as u16;
+    const TAB: u16 = b'\t' as u16;
+    const QUOTE: u16 = b'"' as u16;
+    const BACKSLASH: u16 = b'\\' as u16;
+
+    let is_space = |c: u16| c == SPACE || c == TAB;
+
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    // The program name.
+    if line.is_empty() {
+        return args;
+    }
+
+    let mut program = Vec::new();
+    if line[0] == QUOTE {
+        i += 1;
+        while i < line.len() && line[i] != QUOTE {
+            program.push(line[i]);
+            i += 1;
+        }
+        i += 1; // The closing quote.
+    } else {
+        while i < line.len() && !is_space(line[i]) {
+            program.push(line[i]);
+            i += 1;
+        }
+    }
+    args.push(program);
+
+    while i < line.len() && is_space(line[i]) {
+        i += 1;
+    }
+
+    let mut arg = Vec::new();
+    let mut has_arg = false;
+    let mut in_quotes = false;
+    while i < line.len() {
+        let c = line[i];
+        if c == BACKSLASH {
+            let start = i;
+            while i < line.len() && line[i] == BACKSLASH {
+                i += 1;
+            }
+
+            let count = i - start;
+            let before_quote = line.get(i) == Some(&QUOTE);
+            let backslashes = if before_quote { count / 2 } else { count };
+            arg.extend(std::iter::repeat(BACKSLASH).take(backslashes));
+
+            if before_quote && count % 2 == 1 {
+                arg.push(QUOTE);
+                i += 1;
+            }
+
+            has_arg = true;
+        } else if c == QUOTE {
+            if in_quotes && line.get(i + 1) == Some(&QUOTE) {
+                arg.push(QUOTE);
+                i += 2;
+            } else {
+                in_quotes = !in_quotes;
+                i += 1;
+            }
+
+            has_arg = true;
+        } else if is_space(c) && !in_quotes {
+            if has_arg {
+                args.push(arg);
+                arg = Vec::new();
+                has_arg = false;
+            }
+
+            i += 1;
+        } else {
+            arg.push(c);
+            has_arg = true;
+            i += 1;
+        }
+    }
+
+    if has_arg {
+        args.push(arg);
+    }
+
+    args
+}
@@ -0,0 +1,224 @@
+/*!
+A minimal facade that mimics the clap builder API on top of [`Arguments`].
+
+Only a small subset is supported, which is enough to migrate from clap file by file:
+`Arg::short`, `Arg::long`, `Arg::takes_value`, `Arg::required`, `Arg::default_value`,
+`ArgMatches::value_of` and `ArgMatches::is_present`.
+
+Arguments without `short` and `long` are positional and are parsed in the order they were added.
+
+Unlike clap, keys are written with dashes, like `short("-v")` and `long("--width")`,
+since [`Arguments`] requires `'static` keys.
+
+```
+use pico_args::compat::{App, Arg};
+
+let app = App::new("app")
+    .arg(Arg::new("verbose").short("-v"))
+    .arg(Arg::new("width").long("--width").takes_value(true).default_value("10"))
+    .arg(Arg::new("INPUT").required(true));
+
+let mut args = pico_args::Arguments::from_iter(["-v", "in.txt"].iter().cloned());
+let matches = app.try_get_matches_from(&mut args).unwrap();
+assert!(matches.is_present("verbose"));
+assert_eq!(matches.value_of("width"), Some("10"));
+assert_eq!(matches.value_of("INPUT"), Some("in.txt"));
+```
+
+[`Arguments`]: ../struct.Arguments.html
+*/
+
+use crate::{exit_on_error, Arguments, Error, Keys};
+
+/// An argument description.
+#[derive(Clone, Debug)]
+pub struct Arg {
+    name: &'static str,
+    short: Option<&'static str>,
+    long: Option<&'static str>,
+    takes_value: bool,
+    required: bool,
+    default_value: Option<&'static str>,
+}
+
+impl Arg {
+    /// Creates a new argument with a specified name.
+    ///
+    /// The name is used to access the argument in [`ArgMatches`].
+    ///
+    /// [`ArgMatches`]: struct.ArgMatches.html
+    pub fn new(name: &'static str) -> Self {
+        Arg {
+            name,
+            short: None,
+            long: None,
+            takes_value: false,
+            required: false,
+            default_value: None,
+        }
+    }
+
+    /// Sets a short key with a dash, like `"-v"`.
+    pub fn short(mut self, key: &'static str) -> Self {
+        self.short = Some(key);
+        self
+    }
+
+    /// Sets a long key with dashes, like `"--width"`.
+    pub fn long(mut self, key: &'static str) -> Self {
+        self.long = Some(key);
+        self
+    }
+
+    /// Sets whether the argument is an option with a value or a flag.
+    ///
+    /// Positional arguments always take a value.
+    pub fn takes_value(mut self, yes: bool) -> Self {
+        self.takes_value = yes;
+        self
+    }
+
+    /// Sets whether the argument must be present.
+    pub fn required(mut self, yes: bool) -> Self {
+        self.required = yes;
+        self
+    }
+
+    /// Sets a value that will be used when the argument is not present.
+    ///
+    /// Unlike clap, the argument is not considered present in this case.
+    pub fn default_value(mut self, value: &'static str) -> Self {
+        self.default_value = Some(value);
+        self
+    }
+
+    fn keys(&self) -> Option<Keys> {
+        match (self.short, self.long) {
+            (Some(short), Some(long)) => Some(Keys::from([short, long])),
+            (Some(key), None) | (None, Some(key)) => Some(Keys::from(key)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// An application description.
+#[derive(Clone, Debug)]
+pub struct App {
+    name: &'static str,
+    args: Vec<Arg>,
+}
+
+impl App {
+    /// Creates a new description for an application with a specified name.
+    pub fn new(name: &'static str) -> Self {
+        App { name, args: Vec::new() }
+    }
+
+    /// Returns the application name.
+    pub fn get_name(&self) -> &str {
+        self.name
+    }
+
+    /// Adds an argument.
+    pub fn arg(mut self, arg: Arg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// Parses [`Arguments::from_env`].
+    ///
+    /// Prints an error and exits via [`exit_on_error`] on failure.
+    /// Remaining arguments are ignored.
+    ///
+    /// [`Arguments::from_env`]: ../struct.Arguments.html#method.from_env
+    /// [`exit_on_error`]: ../fn.exit_on_error.html
+    pub fn get_matches(self) -> ArgMatches {
+        exit_on_error(self.try_get_matches_from(&mut Arguments::from_env()))
+    }
+
+    /// Parses arguments according to the description.
+    ///
+    /// Options are parsed before flags and positional arguments to prevent ambiguities.
+    /// Parsed arguments are removed, so the remaining ones can be checked
+    /// via [`Arguments::finish`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// - When a required option is not present.
+    /// - When a required positional argument is not present.
+    /// - When value parsing failed.
+    ///
+    /// [`Arguments::finish`]: ../struct.Arguments.html#method.finish
+    pub fn try_get_matches_from(&self, args: &mut Arguments) -> Result<ArgMatches, Error> {
+        let mut values = Vec::with_capacity(self.args.len());
+        for arg in self.args.iter().filter(|arg| arg.takes_value) {
+            if let Some(keys) = arg.keys() {
+                let value = match args.opt_value_str(keys)? {
+                    Some(value) => Some(value.into_owned()),
+                    None if arg.required && arg.default_value.is_none() => {
+                        return Err(Error::MissingOption(keys));
+                    }
+                    None => None,
+                };
+                values.push((arg, value));
+            }
+        }
+
+        for arg in self.args.iter().filter(|arg| !arg.takes_value) {
+            if let Some(keys) = arg.keys() {
+                let present = args.contains(keys);
+                if !present && arg.required {
+                    return Err(Error::MissingOption(keys));
+                }
+                values.push((arg, if present { Some(String::new()) } else { None }));
+            }
+        }
+
+        for arg in self.args.iter().filter(|arg| arg.keys().is_none()) {
+            let value = match args.opt_free_from_str::<String>()? {
+                Some(value) => Some(value),
+                None if arg.required && arg.default_value.is_none() => {
+                    return Err(Error::MissingArgument);
+                }
+                None => None,
+            };
+            values.push((arg, value));
+        }
+
+        Ok(ArgMatches {
+            values: values.into_iter()
+                .map(|(arg, value)| {
+                    let present = value.is_some();
+                    let value = if arg.takes_value || arg.keys().is_none() {
+                        value.or_else(|| arg.default_value.map(String::from))
+                    } else {
+                        None
+                    };
+                    (arg.name, present, value)
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Parsed arguments.
+#[derive(Clone, Debug)]
+pub struct ArgMatches {
+    values: Vec<(&'static str, bool, Option<String>)>,
+}
+
+impl ArgMatches {
+    /// Checks that an argument was present.
+    pub fn is_present(&self, name: &str) -> bool {
+        self.values.iter().any(|(n, present, _)| *n == name && *present)
+    }
+
+    /// Returns an argument value or its default value.
+    ///
+    /// Always returns `None` for flags.
+    pub fn value_of(&self, name: &str) -> Option<&str> {
+        self.values.iter()
+            .find(|(n, _, _)| *n == name)
+            .and_then(|(_, _, value)| value.as_deref())
+    }
+}
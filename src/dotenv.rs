@@ -0,0 +1,41 @@
+use std::io;
+
+// Parses `KEY=VALUE` lines.
+//
+// Empty lines and lines starting with `#` are skipped. An optional `export ` prefix is allowed.
+// Values are trimmed and matching surrounding quotes are removed, without escapes processing.
+pub(crate) fn parse(text: &str) -> io::Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = match line.strip_prefix("export ") {
+            Some(rest) => rest.trim_start(),
+            None => line,
+        };
+
+        let idx = match line.find('=') {
+            Some(idx) if idx > 0 => idx,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected a KEY=VALUE pair", n + 1),
+                ));
+            }
+        };
+
+        let value = line[idx + 1..].trim();
+        let bytes = value.as_bytes();
+        let quoted = bytes.len() >= 2
+            && (bytes[0] == b'"' || bytes[0] == b'\'')
+            && bytes[0] == bytes[bytes.len() - 1];
+        let value = if quoted { &value[1..value.len() - 1] } else { value };
+
+        vars.push((line[..idx].trim_end().to_string(), value.to_string()));
+    }
+
+    Ok(vars)
+}
@@ -0,0 +1,151 @@
+/*!
+Help message helpers.
+
+```
+use pico_args::help::Table;
+
+let mut table = Table::new();
+table.row("-h, --help", "", "Prints help information");
+table.row("--width", "WIDTH", "Sets width");
+assert_eq!(table.to_string(), concat!(
+    "  -h, --help     Prints help information\n",
+    "  --width WIDTH  Sets width\n",
+));
+```
+*/
+
+use std::fmt::{self, Display};
+
+#[derive(Clone, Debug)]
+enum Row {
+    Section(String),
+    Item(String, String),
+}
+
+/// A two-column table with aligned and wrapped descriptions.
+///
+/// Keys and placeholders are placed in the left column and descriptions in the right one.
+/// Descriptions that do not fit into the total width are wrapped at word boundaries.
+#[derive(Clone, Debug)]
+pub struct Table {
+    rows: Vec<Row>,
+    width: usize,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+impl Table {
+    /// Creates an empty table with the total width of 80 characters.
+    pub fn new() -> Self {
+        Table { rows: Vec::new(), width: 80 }
+    }
+
+    /// Sets the total width.
+    ///
+    /// Descriptions are never wrapped narrower than 20 characters.
+    pub fn set_width(&mut self, width: usize) -> &mut Self {
+        self.width = width;
+        self
+    }
+
+    /// Adds a section title, like `OPTIONS`.
+    ///
+    /// Rows in all sections are aligned to the same column.
+    pub fn section(&mut self, title: &str) -> &mut Self {
+        self.rows.push(Row::Section(title.to_string()));
+        self
+    }
+
+    /// Adds a row.
+    ///
+    /// `placeholder` can be empty for flags and free-standing arguments.
+    pub fn row(&mut self, keys: &str, placeholder: &str, description: &str) -> &mut Self {
+        let left = if placeholder.is_empty() {
+            keys.to_string()
+        } else {
+            format!("{} {}", keys, placeholder)
+        };
+
+        self.rows.push(Row::Item(left, description.to_string()));
+        self
+    }
+}
+
+const INDENT: usize = 2;
+const GAP: usize = 2;
+const MIN_WIDTH: usize = 20;
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let column = self.rows.iter()
+            .filter_map(|row| match row {
+                Row::Item(left, _) => Some(left.chars().count()),
+                Row::Section(_) => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let offset = INDENT + column + GAP;
+        let width = std::cmp::max(self.width.saturating_sub(offset), MIN_WIDTH);
+
+        for (i, row) in self.rows.iter().enumerate() {
+            match row {
+                Row::Section(title) => {
+                    if i != 0 {
+                        writeln!(f)?;
+                    }
+                    writeln!(f, "{}:", title)?;
+                }
+                Row::Item(left, description) => {
+                    let lines = wrap(description, width);
+                    if lines.is_empty() {
+                        writeln!(f, "{:indent$}{}", "", left, indent = INDENT)?;
+                        continue;
+                    }
+
+                    for (n, line) in lines.iter().enumerate() {
+                        let left = if n == 0 { left.as_str() } else { "" };
+                        writeln!(f, "{:indent$}{:column$}{:gap$}{}", "", left, "", line,
+                                 indent = INDENT, column = column, gap = GAP)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Splits text into lines that are not wider than `width`.
+//
+// Explicit line breaks are preserved and words longer than `width` are not split.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let word_width = word.chars().count();
+            if line_width != 0 && line_width + 1 + word_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+
+            if line_width != 0 {
+                line.push(' ');
+                line_width += 1;
+            }
+
+            line.push_str(word);
+            line_width += word_width;
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
@@ -0,0 +1,640 @@
+/*!
+Parsers for commonly used value types.
+
+All parsers can be passed to `*_from_fn` methods directly.
+
+```
+use std::time::Duration;
+use pico_args::helpers;
+
+let mut args = pico_args::Arguments::from_vec(vec!["--timeout".into(), "30s".into()]);
+let timeout = args.value_from_fn("--timeout", helpers::parse_duration).unwrap();
+assert_eq!(timeout, Duration::from_secs(30));
+```
+*/
+
+use std::time::Duration;
+
+/// Parses a duration with a unit suffix.
+///
+/// Supported suffixes are `ms`, `s`, `m`, `h` and `d`. A number without a suffix is in seconds.
+/// The number can have a fractional part, like `1.5h`.
+///
+/// # Errors
+///
+/// - When the number is invalid or negative.
+/// - When the suffix is unknown.
+/// - When the duration is too large.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    if number.is_empty() {
+        return Err(format!("'{}' is not a duration", s));
+    }
+
+    let millis_per_unit: u64 = match suffix {
+        "ms" => 1,
+        "" | "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        "d" => 24 * 60 * 60 * 1000,
+        _ => return Err(format!("unknown duration unit '{}', expected ms, s, m, h or d", suffix)),
+    };
+
+    let too_large = || format!("duration '{}' is too large", s);
+    if number.contains('.') {
+        let number: f64 = number.parse().map_err(|_| format!("invalid number '{}'", number))?;
+        let secs = number * millis_per_unit as f64 / 1000.0;
+        if secs >= u64::MAX as f64 {
+            return Err(too_large());
+        }
+
+        Ok(Duration::from_secs_f64(secs))
+    } else {
+        let number: u64 = number.parse().map_err(|_| too_large())?;
+        number.checked_mul(millis_per_unit).map(Duration::from_millis).ok_or_else(too_large)
+    }
+}
+
+/// Parses a size in bytes with an optional unit suffix.
+///
+/// Supported suffixes are decimal `K`, `M`, `G`, `T` and binary `Ki`, `Mi`, `Gi`, `Ti`,
+/// optionally followed by `B`, like `10MiB` or `5KB`. A lowercase `k` is accepted as well.
+///
+/// # Errors
+///
+/// - When the number is invalid.
+/// - When the suffix is unknown.
+/// - When the size does not fit into `u64`.
+pub fn parse_bytes(s: &str) -> Result<u64, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    if number.is_empty() {
+        return Err(format!("'{}' is not a size", s));
+    }
+
+    let suffix = suffix.strip_suffix('B').unwrap_or(suffix);
+    let multiplier: u64 = match suffix {
+        "" => 1,
+        "K" | "k" => 1_000,
+        "M" => 1_000_000,
+        "G" => 1_000_000_000,
+        "T" => 1_000_000_000_000,
+        "Ki" => 1 << 10,
+        "Mi" => 1 << 20,
+        "Gi" => 1 << 30,
+        "Ti" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "unknown size unit in '{}', expected K, M, G, T, Ki, Mi, Gi or Ti", s
+            ));
+        }
+    };
+
+    let too_large = || format!("size '{}' is too large", s);
+    let number: u64 = number.parse().map_err(|_| too_large())?;
+    number.checked_mul(multiplier).ok_or_else(too_large)
+}
+
+/// Integer types supported by [`parse_int`].
+///
+/// [`parse_int`]: fn.parse_int.html
+pub trait Integer: Sized {
+    /// Converts a string in a given base to an integer.
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),+) => {
+        $(
+            impl Integer for $t {
+                fn from_str_radix(s: &str, radix: u32) -> Result<Self, std::num::ParseIntError> {
+                    <$t>::from_str_radix(s, radix)
+                }
+            }
+        )+
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Parses an integer with an optional base prefix.
+///
+/// Supports `0x` (hex), `0o` (octal) and `0b` (binary) prefixes, an optional leading `-`
+/// and `_` separators, like `0o755`, `0xFF_FF` or `1_000`.
+///
+/// # Errors
+///
+/// - When the number is invalid or does not fit into `T`.
+pub fn parse_int<T: Integer>(s: &str) -> Result<T, String> {
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s),
+    };
+
+    let (radix, digits) = match unsigned.get(..2) {
+        Some("0x") | Some("0X") => (16, &unsigned[2..]),
+        Some("0o") | Some("0O") => (8, &unsigned[2..]),
+        Some("0b") | Some("0B") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+
+    // Do not allow a sign after the prefix or leading separators.
+    if digits.starts_with(['-', '+', '_']) {
+        return Err(format!("invalid number '{}'", s));
+    }
+
+    let number = format!("{}{}", sign, digits.replace('_', ""));
+    T::from_str_radix(&number, radix).map_err(|e| format!("invalid number '{}': {}", s, e))
+}
+
+/// Parses a socket address.
+///
+/// Accepts `host:port`, `[ipv6]:port` and a bare `port`, which is bound to `127.0.0.1`.
+/// An empty host, like in `:8080`, is bound to `0.0.0.0`.
+/// The host must be an IP address or `localhost`, since no name resolution is performed.
+///
+/// # Errors
+///
+/// - When the host or the port is invalid. The message mentions which part was wrong.
+pub fn parse_socket_addr(s: &str) -> Result<std::net::SocketAddr, String> {
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    let parse_port = |port: &str| {
+        port.parse::<u16>().map_err(|_| format!("invalid port '{}' in '{}'", port, s))
+    };
+
+    if !s.contains(':') {
+        return Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), parse_port(s)?));
+    }
+
+    let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+        match rest.find("]:") {
+            Some(idx) => (&rest[..idx], &rest[idx + 2..]),
+            None => return Err(format!("expected '[address]:port' in '{}'", s)),
+        }
+    } else {
+        let idx = s.rfind(':').unwrap_or(0);
+        if s[..idx].contains(':') {
+            return Err(format!("IPv6 address must be enclosed in brackets in '{}'", s));
+        }
+
+        (&s[..idx], &s[idx + 1..])
+    };
+
+    let ip = if host.is_empty() {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    } else if host == "localhost" {
+        IpAddr::V4(Ipv4Addr::LOCALHOST)
+    } else {
+        host.parse().map_err(|_| format!("invalid IP address '{}' in '{}'", host, s))?
+    };
+
+    Ok(SocketAddr::new(ip, parse_port(port)?))
+}
+
+/// Parses an inclusive range.
+///
+/// Accepts `A-B`, `A..B`, `A..=B` and a single value `A`, which is parsed as `A..=A`.
+/// All of them are inclusive, like in `--lines 10-20`.
+///
+/// # Errors
+///
+/// - When a bound cannot be parsed.
+/// - When the start is greater than the end.
+pub fn parse_range<T>(s: &str) -> Result<std::ops::RangeInclusive<T>, String>
+where
+    T: std::str::FromStr + PartialOrd,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    // A leading `-` is a sign and not a separator.
+    let (start, end) = if let Some(idx) = s.find("..=") {
+        (&s[..idx], &s[idx + 3..])
+    } else if let Some(idx) = s.find("..") {
+        (&s[..idx], &s[idx + 2..])
+    } else if let Some(idx) = s.get(1..).and_then(|rest| rest.find('-')) {
+        (&s[..idx + 1], &s[idx + 2..])
+    } else {
+        (s, s)
+    };
+
+    let parse = |bound: &str| {
+        bound.parse::<T>().map_err(|e| format!("invalid number '{}' in '{}': {}", bound, s, e))
+    };
+
+    let start = parse(start)?;
+    let end = parse(end)?;
+    if start > end {
+        return Err(format!("the start of the range '{}' is greater than the end", s));
+    }
+
+    Ok(start..=end)
+}
+
+/// Parses a comma-separated list.
+///
+/// Elements are trimmed before parsing, so `1, 2, 3` is allowed. An empty string is an empty list.
+///
+/// # Errors
+///
+/// - When an element cannot be parsed. The message mentions its index.
+pub fn parse_list<T>(s: &str) -> Result<Vec<T>, String>
+where
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    s.split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(i, item)| {
+            item.parse().map_err(|e| format!("invalid list element #{} '{}': {}", i + 1, item, e))
+        })
+        .collect()
+}
+
+/// A `--color WHEN` option value.
+///
+/// ```
+/// use pico_args::helpers::ColorChoice;
+///
+/// let mut args = pico_args::Arguments::from_vec(vec!["--color".into(), "never".into()]);
+/// let color: ColorChoice = args.opt_value_from_str("--color").unwrap().unwrap_or_default();
+/// assert!(!color.should_colorize(true));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ColorChoice {
+    /// Colorize when writing to a terminal and `NO_COLOR` is not set.
+    ///
+    /// The default.
+    Auto,
+    /// Always colorize.
+    Always,
+    /// Never colorize.
+    Never,
+}
+
+impl Default for ColorChoice {
+    fn default() -> Self {
+        ColorChoice::Auto
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    /// Accepts `auto`, `always`, `ansi` (same as `always`) and `never`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" | "ansi" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("invalid color choice '{}', expected auto, always or never", s)),
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Checks that the output should be colorized.
+    ///
+    /// `is_tty` indicates that the output is a terminal.
+    /// In the `Auto` mode, colors are disabled when the `NO_COLOR` environment variable
+    /// is set and is not empty.
+    pub fn should_colorize(self, is_tty: bool) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                let no_color = match std::env::var_os("NO_COLOR") {
+                    Some(v) => !v.is_empty(),
+                    None => false,
+                };
+                is_tty && !no_color
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// A verbosity level set by repeated `-v` and `-q` flags.
+///
+/// ```
+/// use pico_args::helpers::Verbosity;
+///
+/// let mut args = pico_args::Arguments::from_vec(vec!["-v".into(), "-v".into(), "-q".into()]);
+/// let verbosity = Verbosity::from_args(&mut args);
+/// assert_eq!(verbosity.level(), 1);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Verbosity {
+    verbose: u8,
+    quiet: u8,
+}
+
+impl Verbosity {
+    /// Consumes all `-v`/`--verbose` and `-q`/`--quiet` flags.
+    ///
+    /// With the `combined-flags` feature, `-vvv` is counted as three flags.
+    pub fn from_args(args: &mut crate::Arguments) -> Self {
+        let mut verbosity = Verbosity::default();
+        while args.contains(["-v", "--verbose"]) {
+            verbosity.verbose = verbosity.verbose.saturating_add(1);
+        }
+
+        while args.contains(["-q", "--quiet"]) {
+            verbosity.quiet = verbosity.quiet.saturating_add(1);
+        }
+
+        verbosity
+    }
+
+    /// Returns the number of `-v` flags.
+    pub fn verbose(&self) -> u8 {
+        self.verbose
+    }
+
+    /// Returns the number of `-q` flags.
+    pub fn quiet(&self) -> u8 {
+        self.quiet
+    }
+
+    /// Returns the number of `-v` flags minus the number of `-q` flags.
+    ///
+    /// Zero is the default level. Can be mapped to a logger level by an application.
+    ///
+    /// There is no `log_level_filter` method, since `pico-args` doesn't depend on `log`.
+    /// The mapping is a single `match` on the caller side:
+    ///
+    /// ```ignore
+    /// let filter = match verbosity.level() {
+    ///     i16::MIN..=-1 => log::LevelFilter::Off,
+    ///     0 => log::LevelFilter::Warn,
+    ///     1 => log::LevelFilter::Info,
+    ///     2 => log::LevelFilter::Debug,
+    ///     _ => log::LevelFilter::Trace,
+    /// };
+    /// ```
+    pub fn level(&self) -> i16 {
+        i16::from(self.verbose) - i16::from(self.quiet)
+    }
+}
+
+/// An input file or stdin.
+///
+/// `-` means stdin and anything else is a path.
+///
+/// ```no_run
+/// use std::io::Read;
+/// use pico_args::helpers::Input;
+///
+/// let mut args = pico_args::Arguments::from_env();
+/// let input = args.free_from_os_str(Input::from_os_str).unwrap();
+/// let mut text = String::new();
+/// input.open().unwrap().read_to_string(&mut text).unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+    /// Stdin.
+    Stdin,
+    /// A file path.
+    Path(std::path::PathBuf),
+}
+
+impl Input {
+    /// Parses an input from an argument.
+    ///
+    /// Never fails. The result type allows passing this function to `*_from_os_str` methods.
+    pub fn from_os_str(s: &std::ffi::OsStr) -> Result<Self, std::convert::Infallible> {
+        if s == "-" {
+            Ok(Input::Stdin)
+        } else {
+            Ok(Input::Path(s.into()))
+        }
+    }
+
+    /// Opens the input for reading.
+    ///
+    /// # Errors
+    ///
+    /// - When the file cannot be opened.
+    pub fn open(&self) -> std::io::Result<Box<dyn std::io::Read>> {
+        match self {
+            Input::Stdin => Ok(Box::new(std::io::stdin())),
+            Input::Path(path) => Ok(Box::new(std::fs::File::open(path)?)),
+        }
+    }
+}
+
+impl std::str::FromStr for Input {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Input::from_os_str(s.as_ref())
+    }
+}
+
+/// An output file or stdout.
+///
+/// `-` means stdout and anything else is a path.
+///
+/// ```no_run
+/// use std::io::Write;
+/// use pico_args::helpers::Output;
+///
+/// let mut args = pico_args::Arguments::from_env();
+/// let output = args.value_from_os_str("-o", Output::from_os_str).unwrap();
+/// writeln!(output.create().unwrap(), "Hello").unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Output {
+    /// Stdout.
+    Stdout,
+    /// A file path.
+    Path(std::path::PathBuf),
+}
+
+impl Output {
+    /// Parses an output from an argument.
+    ///
+    /// Never fails. The result type allows passing this function to `*_from_os_str` methods.
+    pub fn from_os_str(s: &std::ffi::OsStr) -> Result<Self, std::convert::Infallible> {
+        if s == "-" {
+            Ok(Output::Stdout)
+        } else {
+            Ok(Output::Path(s.into()))
+        }
+    }
+
+    /// Opens the output for writing.
+    ///
+    /// Stdout is locked on each write. A file is created or truncated.
+    ///
+    /// # Errors
+    ///
+    /// - When the file cannot be created.
+    pub fn create(&self) -> std::io::Result<Box<dyn std::io::Write>> {
+        match self {
+            Output::Stdout => Ok(Box::new(std::io::stdout())),
+            Output::Path(path) => Ok(Box::new(std::fs::File::create(path)?)),
+        }
+    }
+}
+
+impl std::str::FromStr for Output {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Output::from_os_str(s.as_ref())
+    }
+}
+
+/// Parses a percentage into a fraction in the `0..=1` range.
+///
+/// Accepts `50%`, `50` and `0.5`. A number with `%` is always a percentage.
+/// A number without `%` is a fraction when it's in the `0..=1` range and a percentage otherwise,
+/// so `0.5` is 50% and `1.5` is 1.5%. Out of range values are clamped.
+///
+/// # Errors
+///
+/// - When the number is invalid.
+pub fn parse_percent(s: &str) -> Result<f64, String> {
+    parse_percent_in(0.0, 1.0)(s)
+}
+
+/// Returns a percentage parser that clamps values into the `min..=max` range.
+///
+/// Uses the same rules as [`parse_percent`].
+///
+/// ```
+/// use pico_args::helpers;
+///
+/// let parse = helpers::parse_percent_in(0.1, 0.9);
+/// assert_eq!(parse("95%").unwrap(), 0.9);
+/// ```
+///
+/// [`parse_percent`]: fn.parse_percent.html
+pub fn parse_percent_in(min: f64, max: f64) -> impl Fn(&str) -> Result<f64, String> {
+    move |s: &str| {
+        let (number, is_percent) = match s.strip_suffix('%') {
+            Some(number) => (number, true),
+            None => (s, false),
+        };
+
+        let value: f64 = match number.trim().parse() {
+            Ok(v) if f64::is_finite(v) => v,
+            _ => return Err(format!("invalid percentage '{}'", s)),
+        };
+
+        let is_fraction = !is_percent && (0.0..=1.0).contains(&value);
+        let value = if is_fraction { value } else { value / 100.0 };
+        Ok(value.max(min).min(max))
+    }
+}
+
+/// Parses a `KEY=VALUE` pair.
+///
+/// Splits on the first `=`, so the value can contain `=` as well.
+///
+/// ```
+/// use pico_args::helpers;
+///
+/// let mut args = pico_args::Arguments::from_vec(vec!["--env".into(), "JOBS=4".into()]);
+/// let env: Vec<(String, u32)> = args.values_from_fn("--env", helpers::parse_key_val).unwrap();
+/// assert_eq!(env, [("JOBS".to_string(), 4)]);
+/// ```
+///
+/// # Errors
+///
+/// - When there is no `=`.
+/// - When the key or the value cannot be parsed.
+pub fn parse_key_val<K, V>(s: &str) -> Result<(K, V), String>
+where
+    K: std::str::FromStr,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: std::str::FromStr,
+    <V as std::str::FromStr>::Err: std::fmt::Display,
+{
+    let idx = match s.find('=') {
+        Some(idx) => idx,
+        None => return Err(format!("expected a KEY=VALUE pair, got '{}'", s)),
+    };
+
+    let key = &s[..idx];
+    let value = &s[idx + 1..];
+    let key = key.parse().map_err(|e| format!("invalid key '{}': {}", key, e))?;
+    let value = value.parse().map_err(|e| format!("invalid value '{}': {}", value, e))?;
+    Ok((key, value))
+}
+
+/// Parses a path, expanding a leading `~` to the home directory.
+///
+/// Shells don't expand `~` in `--path=~/x` or in arguments loaded from files.
+/// The home directory is taken from `HOME` (or `USERPROFILE` on Windows).
+/// Only `~` and `~/...` are expanded. Since there is no access to the users database,
+/// `~user` is returned as is, as well as any path when the home directory is not set.
+///
+/// Never fails. The result type allows passing this function to `*_from_os_str` methods.
+///
+/// ```no_run
+/// use pico_args::helpers;
+///
+/// let mut args = pico_args::Arguments::from_env();
+/// let config = args.value_from_os_str("--config", helpers::parse_path_expand_tilde).unwrap();
+/// ```
+pub fn parse_path_expand_tilde(
+    s: &std::ffi::OsStr,
+) -> Result<std::path::PathBuf, std::convert::Infallible> {
+    use std::path::{Component, Path, PathBuf};
+
+    let path = Path::new(s);
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Normal(first)) if first == "~" => {}
+        _ => return Ok(path.into()),
+    }
+
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    let home = match std::env::var_os(var) {
+        Some(home) if !home.is_empty() => PathBuf::from(home),
+        _ => return Ok(path.into()),
+    };
+
+    let rest = components.as_path();
+    if rest.as_os_str().is_empty() {
+        Ok(home)
+    } else {
+        Ok(home.join(rest))
+    }
+}
+
+/// Returns a path parser that resolves relative paths against `base`.
+///
+/// Unlike `std::fs::canonicalize` alone, doesn't depend on the current directory,
+/// which is handy for paths that came from a config file.
+/// The result is canonicalized, so the path must exist.
+///
+/// ```no_run
+/// use pico_args::helpers;
+///
+/// let mut args = pico_args::Arguments::from_env();
+/// let parse = helpers::resolve_path("/etc/app");
+/// let include = args.value_from_os_str("--include", parse).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// - When the path cannot be canonicalized.
+pub fn resolve_path<P>(base: P) -> impl Fn(&std::ffi::OsStr) -> Result<std::path::PathBuf, String>
+where
+    P: Into<std::path::PathBuf>,
+{
+    let base = base.into();
+    move |s: &std::ffi::OsStr| {
+        // An absolute path replaces the base.
+        let path = base.join(s);
+        std::fs::canonicalize(&path)
+            .map_err(|e| format!("cannot resolve '{}': {}", path.display(), e))
+    }
+}
@@ -3,7 +3,9 @@ An ultra simple CLI arguments parser.
 
 If you think that this library doesn't support some feature, it's probably intentional.
 
-- No help generation
+- No help generation by default. The `builder` module can describe a CLI, parse it
+  and render help and man pages from the same description
+- Hand-written help messages can be aligned and wrapped via `help::Table`
 - Only flags, options, free arguments and subcommands are supported
 - Options can be separated by a space, `=` or nothing. See build features
 - Arguments can be in any order
@@ -14,6 +16,7 @@ If you think that this library doesn't support some feature, it's probably inten
 - `eq-separator`
 
   Allows parsing arguments separated by `=`<br/>
+  Each parser can restrict separators via `Arguments::set_separator_policy`<br/>
   This feature adds about 1KiB to the resulting binary
 
 - `short-space-opt`
@@ -28,18 +31,119 @@ If you think that this library doesn't support some feature, it's probably inten
   Allows combination of flags, e.g. `-abc` instead of `-a -b -c`<br/>
   If `short-space-opt` or `eq-separator` are enabled, you must parse flags after values,
   to prevent ambiguities
+
+- `color`
+
+  Adds `Error::display_colored`, which highlights the offending key/value
+  using ANSI escape codes<br/>
+  The `NO_COLOR` environment variable is respected
+
+- `utf8-only`
+
+  Stores arguments as `String` instead of `OsString` and removes all `OsStr` methods<br/>
+  `Arguments::from_vec` accepts and `Arguments::finish` returns `Vec<String>` then<br/>
+  `Arguments::from_env` will panic on a non UTF-8 argument<br/>
+  Useful for platforms where arguments are always UTF-8, like WASM
+
+- `dotenv`
+
+  Adds `Arguments::load_dotenv`, which loads `KEY=VALUE` pairs from a `.env` file<br/>
+  Loaded variables are used by `Arguments::extend_from_env` as a fallback
+
+- `wild`
+
+  Expands `*` and `?` wildcards in free-standing arguments on Windows,
+  where the shell doesn't do it<br/>
+  Only the last path component can contain wildcards<br/>
+  Does nothing on other platforms
+
+- `stats`
+
+  Adds `Arguments::stats`, which reports the number of scans, allocations
+  and removed arguments<br/>
+  Useful for measuring the parser overhead
+
+- `min-size-errors`
+
+  Replaces error messages with numeric codes and option keys, like `E3 --width`,
+  which removes most of the formatting code from the binary<br/>
+  See `ErrorKind::code`
 */
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::borrow::Cow;
+#[cfg(not(feature = "utf8-only"))]
 use std::ffi::{OsString, OsStr};
 use std::fmt::{self, Display};
+use std::ops::{Bound, RangeBounds};
 use std::str::FromStr;
 
+mod argfile;
+mod borrowed;
+pub mod builder;
+mod cmdline;
+pub mod compat;
+#[cfg(feature = "dotenv")]
+mod dotenv;
+pub mod help;
+pub mod helpers;
+mod macros;
+mod resolver;
+pub mod testing;
+#[cfg(feature = "wild")]
+mod wild;
+
+pub use argfile::{fits_in_os_limits, write_argfile};
+pub use borrowed::ArgumentsRef;
+pub use resolver::{ConfigSource, Resolver, Source};
+
+/// Commonly used types.
+///
+/// ```
+/// use pico_args::prelude::*;
+/// ```
+pub mod prelude {
+    pub use crate::{
+        ArgSource, Arguments, CommonFlag, Error, ErrorKind, Event, FromArguments, FromStrTuple,
+        FormattedError, Keys, ProbeError, QuoteMode, TryError,
+    };
+    #[cfg(feature = "eq-separator")]
+    pub use crate::SeparatorPolicy;
+    pub use crate::builder::{Cli, Matches};
+    #[cfg(feature = "color")]
+    pub use crate::ColoredError;
+    #[cfg(feature = "stats")]
+    pub use crate::Stats;
+}
+
+#[cfg(not(feature = "utf8-only"))]
+type Arg = OsString;
+#[cfg(not(feature = "utf8-only"))]
+type ArgStr = OsStr;
+
+#[cfg(feature = "utf8-only")]
+type Arg = String;
+#[cfg(feature = "utf8-only")]
+type ArgStr = str;
+
 
 /// A list of possible errors.
-#[derive(Clone, Debug)]
+///
+/// New variants can be added in the future, so prefer [`Error::kind`]
+/// and the accessor methods to matching on variants.
+///
+/// The error is guaranteed to be `Send + Sync + 'static`, so it can be converted
+/// into `Box<dyn std::error::Error + Send + Sync>` or `std::io::Error` via `?`.
+///
+/// `Debug` output doesn't depend on the variant layout and lists the kind,
+/// the key, the value, the cause and the default message, which makes it suitable
+/// for snapshot tests.
+///
+/// [`Error::kind`]: enum.Error.html#method.kind
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Error {
     /// Arguments must be a valid UTF-8 strings.
     NonUtf8Argument,
@@ -57,380 +161,2799 @@ pub enum Error {
     #[allow(missing_docs)]
     Utf8ArgumentParsingFailed { value: String, cause: String },
 
+    /// Failed to parse a UTF-8 option value.
+    #[allow(missing_docs)]
+    OptionParsingFailed { key: &'static str, value: String, cause: String },
+
     /// Failed to parse a raw free-standing argument.
     #[allow(missing_docs)]
     ArgumentParsingFailed { cause: String },
-}
 
-impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Error::NonUtf8Argument => {
-                write!(f, "argument is not a UTF-8 string")
-            }
-            Error::MissingArgument => {
-                write!(f, "free-standing argument is missing")
-            }
-            Error::MissingOption(key) => {
-                if key.second().is_empty() {
-                    write!(f, "the '{}' option must be set", key.first())
-                } else {
-                    write!(f, "the '{}/{}' option must be set", key.first(), key.second())
-                }
-            }
-            Error::OptionWithoutAValue(key) => {
-                write!(f, "the '{}' option doesn't have an associated value", key)
-            }
-            Error::Utf8ArgumentParsingFailed { value, cause } => {
-                write!(f, "failed to parse '{}': {}", value, cause)
-            }
-            Error::ArgumentParsingFailed { cause } => {
-                write!(f, "failed to parse a binary argument: {}", cause)
-            }
-        }
-    }
-}
+    /// A subcommand that is not in the list of known ones.
+    ///
+    /// Contains the closest known subcommand, if any.
+    #[allow(missing_docs)]
+    UnknownSubcommand { name: String, suggestion: Option<&'static str> },
 
-impl std::error::Error for Error {}
+    /// Two options that cannot be used together.
+    ConflictingOptions(&'static str, &'static str),
 
+    /// An option that requires another option, which is missing.
+    #[allow(missing_docs)]
+    MissingRequiredOption { key: &'static str, required: Keys },
 
-#[derive(Clone, Copy, PartialEq)]
-enum PairKind {
-    #[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
-    SingleArgument,
-    TwoArguments,
-}
+    /// An option occurred fewer or more times than allowed.
+    ///
+    /// `max` is `None` when there is no upper bound.
+    #[allow(missing_docs)]
+    OccurrencesOutOfRange { key: &'static str, count: usize, min: usize, max: Option<usize> },
 
+    /// An option value that looks like a flag or an option.
+    ///
+    /// Reported only in the strict values mode.
+    /// Non UTF-8 values are converted lossily.
+    #[allow(missing_docs)]
+    FlagLikeValue { key: &'static str, value: String },
 
-/// An arguments parser.
-#[derive(Clone, Debug)]
-pub struct Arguments(Vec<OsString>);
+    /// An option that takes arguments up to a terminator, which is missing.
+    #[allow(missing_docs)]
+    MissingTerminator { key: &'static str, terminator: &'static str },
 
-impl Arguments {
-    /// Creates a parser from a vector of arguments.
+    /// An option value that refers to an undefined environment variable.
     ///
-    /// The executable path **must** be removed.
+    /// Reported only when environment variables interpolation is enabled.
+    #[allow(missing_docs)]
+    UndefinedVariable { key: &'static str, name: String },
+
+    /// A number of free-standing arguments outside the expected range.
     ///
-    /// This can be used for supporting `--` arguments to forward to another program.
-    /// See `examples/dash_dash.rs` for an example.
-    pub fn from_vec(args: Vec<OsString>) -> Self {
-        Arguments(args)
-    }
+    /// `max` is `None` when there is no upper bound.
+    /// `names` are placeholders of the expected arguments, if known.
+    #[allow(missing_docs)]
+    ArgumentCountOutOfRange {
+        count: usize,
+        min: usize,
+        max: Option<usize>,
+        names: &'static [&'static str],
+    },
 
-    /// Creates a parser from [`env::args_os`].
+    /// A missing named free-standing argument.
     ///
-    /// The executable path will be removed.
+    /// Has the [`ErrorKind::MissingArgument`] kind.
     ///
-    /// [`env::args_os`]: https://doc.rust-lang.org/stable/std/env/fn.args_os.html
-    pub fn from_env() -> Self {
-        let mut args: Vec<_> = std::env::args_os().collect();
-        args.remove(0);
-        Arguments(args)
-    }
+    /// [`ErrorKind::MissingArgument`]: enum.ErrorKind.html#variant.MissingArgument
+    MissingNamedArgument(&'static str),
 
-    /// Parses the name of the subcommand, that is, the first positional argument.
+    /// Failed to parse a named UTF-8 free-standing argument.
     ///
-    /// Returns `None` when subcommand starts with `-` or when there are no arguments left.
+    /// Has the [`ErrorKind::Utf8ArgumentParsingFailed`] kind.
     ///
-    /// # Errors
+    /// [`ErrorKind::Utf8ArgumentParsingFailed`]: enum.ErrorKind.html#variant.Utf8ArgumentParsingFailed
+    #[allow(missing_docs)]
+    NamedArgumentParsingFailed { name: &'static str, value: String, cause: String },
+}
+
+/// A kind of [`Error`].
+///
+/// [`Error`]: enum.Error.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::NonUtf8Argument`](enum.Error.html#variant.NonUtf8Argument).
+    NonUtf8Argument,
+    /// See [`Error::MissingArgument`](enum.Error.html#variant.MissingArgument)
+    /// and [`Error::MissingNamedArgument`](enum.Error.html#variant.MissingNamedArgument).
+    MissingArgument,
+    /// See [`Error::MissingOption`](enum.Error.html#variant.MissingOption).
+    MissingOption,
+    /// See [`Error::OptionWithoutAValue`](enum.Error.html#variant.OptionWithoutAValue).
+    OptionWithoutAValue,
+    /// See [`Error::Utf8ArgumentParsingFailed`](enum.Error.html#variant.Utf8ArgumentParsingFailed)
+    /// and [`Error::NamedArgumentParsingFailed`](enum.Error.html#variant.NamedArgumentParsingFailed).
+    Utf8ArgumentParsingFailed,
+    /// See [`Error::OptionParsingFailed`](enum.Error.html#variant.OptionParsingFailed).
+    OptionParsingFailed,
+    /// See [`Error::ArgumentParsingFailed`](enum.Error.html#variant.ArgumentParsingFailed).
+    ArgumentParsingFailed,
+    /// See [`Error::UnknownSubcommand`](enum.Error.html#variant.UnknownSubcommand).
+    UnknownSubcommand,
+    /// See [`Error::ConflictingOptions`](enum.Error.html#variant.ConflictingOptions).
+    ConflictingOptions,
+    /// See [`Error::MissingRequiredOption`](enum.Error.html#variant.MissingRequiredOption).
+    MissingRequiredOption,
+    /// See [`Error::OccurrencesOutOfRange`](enum.Error.html#variant.OccurrencesOutOfRange).
+    OccurrencesOutOfRange,
+    /// See [`Error::FlagLikeValue`](enum.Error.html#variant.FlagLikeValue).
+    FlagLikeValue,
+    /// See [`Error::MissingTerminator`](enum.Error.html#variant.MissingTerminator).
+    MissingTerminator,
+    /// See [`Error::UndefinedVariable`](enum.Error.html#variant.UndefinedVariable).
+    UndefinedVariable,
+    /// See [`Error::ArgumentCountOutOfRange`](enum.Error.html#variant.ArgumentCountOutOfRange).
+    ArgumentCountOutOfRange,
+}
+
+impl ErrorKind {
+    /// Returns a numeric error code.
     ///
-    /// - When arguments is not a UTF-8 string.
-    pub fn subcommand(&mut self) -> Result<Option<String>, Error> {
-        if self.0.is_empty() {
-            return Ok(None);
+    /// Codes are stable and are never reused, so they can be documented
+    /// by applications instead of messages.
+    /// See the `min-size-errors` build feature.
+    pub fn code(self) -> u8 {
+        match self {
+            ErrorKind::NonUtf8Argument => 1,
+            ErrorKind::MissingArgument => 2,
+            ErrorKind::MissingOption => 3,
+            ErrorKind::OptionWithoutAValue => 4,
+            ErrorKind::Utf8ArgumentParsingFailed => 5,
+            ErrorKind::OptionParsingFailed => 6,
+            ErrorKind::ArgumentParsingFailed => 7,
+            ErrorKind::UnknownSubcommand => 8,
+            ErrorKind::ConflictingOptions => 9,
+            ErrorKind::MissingRequiredOption => 10,
+            ErrorKind::OccurrencesOutOfRange => 11,
+            ErrorKind::FlagLikeValue => 12,
+            ErrorKind::MissingTerminator => 13,
+            ErrorKind::UndefinedVariable => 14,
+            ErrorKind::ArgumentCountOutOfRange => 15,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the error kind.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::NonUtf8Argument => ErrorKind::NonUtf8Argument,
+            Error::MissingArgument | Error::MissingNamedArgument(_) => ErrorKind::MissingArgument,
+            Error::MissingOption(_) => ErrorKind::MissingOption,
+            Error::OptionWithoutAValue(_) => ErrorKind::OptionWithoutAValue,
+            Error::Utf8ArgumentParsingFailed { .. }
+            | Error::NamedArgumentParsingFailed { .. } => ErrorKind::Utf8ArgumentParsingFailed,
+            Error::OptionParsingFailed { .. } => ErrorKind::OptionParsingFailed,
+            Error::ArgumentParsingFailed { .. } => ErrorKind::ArgumentParsingFailed,
+            Error::UnknownSubcommand { .. } => ErrorKind::UnknownSubcommand,
+            Error::ConflictingOptions(..) => ErrorKind::ConflictingOptions,
+            Error::MissingRequiredOption { .. } => ErrorKind::MissingRequiredOption,
+            Error::OccurrencesOutOfRange { .. } => ErrorKind::OccurrencesOutOfRange,
+            Error::FlagLikeValue { .. } => ErrorKind::FlagLikeValue,
+            Error::MissingTerminator { .. } => ErrorKind::MissingTerminator,
+            Error::UndefinedVariable { .. } => ErrorKind::UndefinedVariable,
+            Error::ArgumentCountOutOfRange { .. } => ErrorKind::ArgumentCountOutOfRange,
         }
+    }
 
-        if let Some(s) = self.0[0].to_str() {
-            if s.starts_with('-') {
-                return Ok(None);
+    /// Returns the key of the option that caused the error.
+    ///
+    /// For a missing option, the long key is preferred.
+    pub fn key(&self) -> Option<&'static str> {
+        match self {
+            Error::MissingOption(keys) => {
+                if keys.second().is_empty() { Some(keys.first()) } else { Some(keys.second()) }
             }
+            Error::OptionWithoutAValue(key) => Some(key),
+            Error::OptionParsingFailed { key, .. } => Some(key),
+            Error::ConflictingOptions(key, _) => Some(key),
+            Error::MissingRequiredOption { key, .. } => Some(key),
+            Error::OccurrencesOutOfRange { key, .. } => Some(key),
+            Error::FlagLikeValue { key, .. } => Some(key),
+            Error::MissingTerminator { key, .. } => Some(key),
+            Error::UndefinedVariable { key, .. } => Some(key),
+            _ => None,
         }
+    }
 
-        self.0.remove(0)
-            .into_string()
-            .map_err(|_| Error::NonUtf8Argument)
-            .map(Some)
+    /// Returns the keys of a missing option.
+    pub fn keys(&self) -> Option<Keys> {
+        match self {
+            Error::MissingOption(keys) => Some(*keys),
+            Error::MissingRequiredOption { required, .. } => Some(*required),
+            _ => None,
+        }
     }
 
-    /// Checks that arguments contain a specified flag.
-    ///
-    /// Searches through all arguments, not only the first/next one.
-    ///
-    /// Calling this method "consumes" the flag: if a flag is present `n`
-    /// times then the first `n` calls to `contains` for that flag will
-    /// return `true`, and subsequent calls will return `false`.
+    /// Returns the value that caused the error.
     ///
-    /// When the "combined-flags" feature is used, repeated letters count
-    /// as repeated flags: `-vvv` is treated the same as `-v -v -v`.
-    pub fn contains<A: Into<Keys>>(&mut self, keys: A) -> bool {
-        self.contains_impl(keys.into())
+    /// For an unknown subcommand, this is its name.
+    /// For an undefined variable, this is the variable name.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Error::Utf8ArgumentParsingFailed { value, .. } => Some(value),
+            Error::NamedArgumentParsingFailed { value, .. } => Some(value),
+            Error::OptionParsingFailed { value, .. } => Some(value),
+            Error::UnknownSubcommand { name, .. } => Some(name),
+            Error::FlagLikeValue { value, .. } => Some(value),
+            Error::UndefinedVariable { name, .. } => Some(name),
+            _ => None,
+        }
     }
 
-    #[inline(never)]
-    fn contains_impl(&mut self, keys: Keys) -> bool {
-        if let Some((idx, _)) = self.index_of(keys) {
-            self.0.remove(idx);
-            true
-        } else {
-            #[cfg(feature = "combined-flags")]
-            // Combined flags only work if the short flag is a single character
-            {
-                if keys.first().len() == 2 {
-                    let short_flag = &keys.first()[1..2];
-                    for (n, item) in self.0.iter().enumerate() {
-                        if let Some(s) = item.to_str() {
-                            if s.starts_with('-') && !s.starts_with("--") && s.contains(short_flag) {
-                                if s.len() == 2 {
-                                    // last flag
-                                    self.0.remove(n);
-                                } else {
-                                    self.0[n] = s.replacen(short_flag, "", 1).into();
-                                }
-                                return true;
-                            }
-                        }
-                    }
-                }
-            }
-            false
+    /// Returns the error message of a parsing function.
+    pub fn cause(&self) -> Option<&str> {
+        match self {
+            Error::Utf8ArgumentParsingFailed { cause, .. } => Some(cause),
+            Error::NamedArgumentParsingFailed { cause, .. } => Some(cause),
+            Error::OptionParsingFailed { cause, .. } => Some(cause),
+            Error::ArgumentParsingFailed { cause } => Some(cause),
+            _ => None,
         }
     }
 
-    /// Parses a key-value pair using `FromStr` trait.
-    ///
-    /// This is a shorthand for `value_from_fn("--key", FromStr::from_str)`
-    pub fn value_from_str<A, T>(&mut self, keys: A) -> Result<T, Error>
-    where
-        A: Into<Keys>,
-        T: FromStr,
-        <T as FromStr>::Err: Display,
-    {
-        self.value_from_fn(keys, FromStr::from_str)
+    /// Returns the closest known subcommand for an unknown one.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Error::UnknownSubcommand { suggestion, .. } => *suggestion,
+            _ => None,
+        }
     }
 
-    /// Parses a key-value pair using a specified function.
+    /// Returns a conventional process exit code for the error.
     ///
-    /// Searches through all argument, not only the first/next one.
-    ///
-    /// When a key-value pair is separated by a space, the algorithm
-    /// will threat the next argument after the key as a value,
-    /// even if it has a `-/--` prefix.
-    /// So a key-value pair like `--key --value` is not an error.
+    /// Follows BSD `sysexits.h`: 65 (`EX_DATAERR`) when a value is malformed
+    /// and 64 (`EX_USAGE`) for all other errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NonUtf8Argument
+            | Error::Utf8ArgumentParsingFailed { .. }
+            | Error::NamedArgumentParsingFailed { .. }
+            | Error::OptionParsingFailed { .. }
+            | Error::ArgumentParsingFailed { .. } => 65,
+            _ => 64,
+        }
+    }
+}
+
+/// Unwraps a result or exits the process.
+///
+/// On error, prints `Error: <message>.` to stderr and exits with [`Error::exit_code`].
+///
+/// # Example
+///
+/// ```no_run
+/// let mut args = pico_args::Arguments::from_env();
+/// let width: u32 = pico_args::exit_on_error(args.value_from_str("--width"));
+/// println!("{}", width);
+/// ```
+///
+/// [`Error::exit_code`]: enum.Error.html#method.exit_code
+pub fn exit_on_error<T>(result: Result<T, Error>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(e.exit_code());
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_painted(f, Palette::PLAIN)
+    }
+}
+
+impl Error {
+    /// Returns a wrapper that displays the error using the provided function.
     ///
-    /// Must be used only once for each option.
+    /// Allows rendering error messages in other languages.
+    /// The `Display` implementation of `Error` can be used as a fallback
+    /// for untranslated errors.
     ///
-    /// # Errors
+    /// # Example
     ///
-    /// - When option is not present.
-    /// - When key or value is not a UTF-8 string. Use [`value_from_os_str`] instead.
-    /// - When value parsing failed.
-    /// - When key-value pair is separated not by space or `=`.
+    /// ```
+    /// use std::fmt;
+    /// use pico_args::{Error, ErrorKind};
     ///
-    /// [`value_from_os_str`]: struct.Arguments.html#method.value_from_os_str
-    pub fn value_from_fn<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<T, Error> {
-        let keys = keys.into();
-        match self.opt_value_from_fn(keys, f) {
-            Ok(Some(v)) => Ok(v),
-            Ok(None) => Err(Error::MissingOption(keys)),
-            Err(e) => Err(e),
-        }
-    }
-
-    /// Parses an optional key-value pair using `FromStr` trait.
+    /// fn german(e: &Error, f: &mut fmt::Formatter) -> fmt::Result {
+    ///     match e.kind() {
+    ///         ErrorKind::MissingArgument => write!(f, "freies Argument fehlt"),
+    ///         _ => fmt::Display::fmt(e, f),
+    ///     }
+    /// }
     ///
-    /// This is a shorthand for `opt_value_from_fn("--key", FromStr::from_str)`
-    pub fn opt_value_from_str<A, T>(&mut self, keys: A) -> Result<Option<T>, Error>
+    /// let e = Error::MissingArgument;
+    /// assert_eq!(e.display_with(german).to_string(), "freies Argument fehlt");
+    /// assert_eq!(e.to_string(), "free-standing argument is missing");
+    /// ```
+    pub fn display_with<F>(&self, formatter: F) -> FormattedError<'_, F>
     where
-        A: Into<Keys>,
-        T: FromStr,
-        <T as FromStr>::Err: Display,
+        F: Fn(&Error, &mut fmt::Formatter) -> fmt::Result,
     {
-        self.opt_value_from_fn(keys, FromStr::from_str)
+        FormattedError { error: self, formatter }
     }
 
-    /// Parses an optional key-value pair using a specified function.
-    ///
-    /// The same as [`value_from_fn`], but returns `Ok(None)` when option is not present.
+    /// Returns a wrapper that displays the error with the offending key
+    /// highlighted in yellow and the offending value in red.
     ///
-    /// [`value_from_fn`]: struct.Arguments.html#method.value_from_fn
-    pub fn opt_value_from_fn<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<Option<T>, Error> {
-        self.opt_value_from_fn_impl(keys.into(), f)
+    /// Plain ANSI escape codes are used.
+    /// Colors are disabled when the `NO_COLOR` environment variable is set
+    /// and is not empty.
+    #[cfg(feature = "color")]
+    pub fn display_colored(&self) -> ColoredError<'_> {
+        let no_color = match std::env::var_os("NO_COLOR") {
+            Some(v) => !v.is_empty(),
+            None => false,
+        };
+        ColoredError {
+            error: self,
+            palette: if no_color { Palette::PLAIN } else { Palette::COLORED },
+        }
     }
 
     #[inline(never)]
-    fn opt_value_from_fn_impl<T, E: Display>(
-        &mut self,
-        keys: Keys,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<Option<T>, Error> {
-        match self.find_value(keys)? {
-            Some((value, kind, idx)) => {
-                match f(value) {
-                    Ok(value) => {
-                        // Remove only when all checks are passed.
-                        self.0.remove(idx);
-                        if kind == PairKind::TwoArguments {
-                            self.0.remove(idx);
-                        }
+    fn fmt_painted(&self, f: &mut fmt::Formatter, p: Palette) -> fmt::Result {
+        // Messages are removed by the optimizer.
+        if cfg!(feature = "min-size-errors") {
+            return self.fmt_compact(f);
+        }
 
-                        Ok(Some(value))
-                    }
-                    Err(e) => {
-                        Err(Error::Utf8ArgumentParsingFailed {
-                            value: value.to_string(),
-                            cause: error_to_string(e),
-                        })
-                    }
+        match self {
+            Error::NonUtf8Argument => {
+                write!(f, "argument is not a UTF-8 string")
+            }
+            Error::MissingArgument => {
+                write!(f, "free-standing argument is missing")
+            }
+            Error::MissingOption(key) => {
+                if key.second().is_empty() {
+                    write!(f, "the '{}' option must be set", Paint(key.first(), p.key))
+                } else {
+                    write!(f, "the '{}/{}' option must be set",
+                           Paint(key.first(), p.key), Paint(key.second(), p.key))
+                }
+            }
+            Error::OptionWithoutAValue(key) => {
+                write!(f, "the '{}' option doesn't have an associated value", Paint(key, p.key))
+            }
+            Error::Utf8ArgumentParsingFailed { value, cause } => {
+                write!(f, "failed to parse '{}': {}", Paint(value, p.value), cause)
+            }
+            Error::OptionParsingFailed { key, value, cause } => {
+                write!(f, "failed to parse '{} {}': {}",
+                       Paint(key, p.key), Paint(value, p.value), cause)
+            }
+            Error::ArgumentParsingFailed { cause } => {
+                write!(f, "failed to parse a binary argument: {}", cause)
+            }
+            Error::UnknownSubcommand { name, suggestion } => {
+                write!(f, "unknown subcommand '{}'", Paint(name, p.value))?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean '{}'?", Paint(suggestion, p.key))?;
                 }
+                Ok(())
+            }
+            Error::ConflictingOptions(a, b) => {
+                write!(f, "the '{}' option cannot be used with '{}'",
+                       Paint(a, p.key), Paint(b, p.key))
+            }
+            Error::MissingRequiredOption { key, required } => {
+                write!(f, "the '{}' option requires '{}'", Paint(key, p.key), Paint(required, p.key))
+            }
+            Error::OccurrencesOutOfRange { key, count, min, max } => {
+                if count < min {
+                    write!(f, "expected at least {} '{}', got {}", min, Paint(key, p.key), count)
+                } else {
+                    write!(f, "expected at most {} '{}', got {}",
+                           max.unwrap_or(0), Paint(key, p.key), count)
+                }
+            }
+            Error::FlagLikeValue { key, value } => {
+                write!(f, "the '{}' option expects a value, got '{}'",
+                       Paint(key, p.key), Paint(value, p.value))
+            }
+            Error::MissingTerminator { key, terminator } => {
+                write!(f, "the '{}' option must be terminated by '{}'",
+                       Paint(key, p.key), Paint(terminator, p.value))
+            }
+            Error::UndefinedVariable { key, name } => {
+                write!(f, "the '{}' option refers to an undefined variable '{}'",
+                       Paint(key, p.key), Paint(name, p.value))
+            }
+            Error::MissingNamedArgument(name) => {
+                write!(f, "the <{}> argument is missing", Paint(name, p.key))
+            }
+            Error::NamedArgumentParsingFailed { name, value: _, cause } => {
+                write!(f, "invalid value for <{}>: {}", Paint(name, p.key), cause)
+            }
+            Error::ArgumentCountOutOfRange { count, min, max, names } => {
+                let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+                match (*min, *max) {
+                    (min, Some(max)) if min == max => write!(f, "expected {} {}", min, plural(min))?,
+                    (0, Some(max)) => write!(f, "expected at most {} {}", max, plural(max))?,
+                    (min, Some(max)) => write!(f, "expected {} to {} arguments", min, max)?,
+                    (min, None) => write!(f, "expected at least {} {}", min, plural(min))?,
+                }
+                if !names.is_empty() {
+                    write!(f, " ({})", names.join(", "))?;
+                }
+                write!(f, ", got {}", count)
             }
-            None => Ok(None),
         }
     }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("kind", &self.kind())
+            .field("key", &self.key())
+            .field("value", &self.value())
+            .field("cause", &self.cause())
+            .field("message", &self.to_string())
+            .finish()
+    }
+}
+
+impl Error {
+    fn fmt_compact(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "E{}", self.kind().code())?;
+        if let Some(key) = self.key() {
+            write!(f, " {}", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for std::io::Error {
+    /// Creates an error of the `InvalidInput` kind.
+    fn from(e: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+    }
+}
+
+
+/// An error returned by the `try_*` methods.
+///
+/// Unlike [`Error`], preserves the error returned by a parsing function,
+/// so it can be converted into an application error via `From`.
+///
+/// [`Error`]: enum.Error.html
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TryError<E> {
+    /// An arguments error, like a missing option or a missing value.
+    Args(Error),
+
+    /// An error returned by a parsing function.
+    Value(E),
+}
+
+impl<E: Display> Display for TryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryError::Args(e) => e.fmt(f),
+            TryError::Value(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for TryError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TryError::Args(e) => Some(e),
+            TryError::Value(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<Error> for TryError<E> {
+    fn from(e: Error) -> Self {
+        TryError::Args(e)
+    }
+}
+
+/// An error returned by [`Arguments::probe_value_from_fn`].
+///
+/// Unlike [`Error`], doesn't own any data, so creating it doesn't allocate.
+/// The offending argument can be found by its position in the original arguments.
+///
+/// [`Arguments::probe_value_from_fn`]: struct.Arguments.html#method.probe_value_from_fn
+/// [`Error`]: enum.Error.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ProbeError {
+    kind: ErrorKind,
+    key: Option<&'static str>,
+    position: Option<usize>,
+}
+
+impl ProbeError {
+    /// Returns the error kind.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the key of the option that caused the error.
+    pub fn key(&self) -> Option<&'static str> {
+        self.key
+    }
+
+    /// Returns the original position of the offending argument.
+    ///
+    /// This is the value position for a parsing error and the key position otherwise.
+    /// Positions are zero-based and do not include the executable path.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+}
+
+
+/// An [`Error`] wrapper that renders messages using a custom function.
+///
+/// Created by [`Error::display_with`].
+///
+/// [`Error`]: enum.Error.html
+/// [`Error::display_with`]: enum.Error.html#method.display_with
+#[derive(Clone, Copy)]
+pub struct FormattedError<'a, F> {
+    error: &'a Error,
+    formatter: F,
+}
+
+impl<F> Display for FormattedError<'_, F>
+where
+    F: Fn(&Error, &mut fmt::Formatter) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.formatter)(self.error, f)
+    }
+}
+
+/// An [`Error`] wrapper that renders highlighted messages.
+///
+/// Created by [`Error::display_colored`].
+///
+/// [`Error`]: enum.Error.html
+/// [`Error::display_colored`]: enum.Error.html#method.display_colored
+#[cfg(feature = "color")]
+#[derive(Clone, Copy, Debug)]
+pub struct ColoredError<'a> {
+    error: &'a Error,
+    palette: Palette,
+}
+
+#[cfg(feature = "color")]
+impl Display for ColoredError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt_painted(f, self.palette)
+    }
+}
+
+// ANSI SGR codes. An empty string disables highlighting.
+#[derive(Clone, Copy, Debug)]
+struct Palette {
+    key: &'static str,
+    value: &'static str,
+}
+
+impl Palette {
+    const PLAIN: Palette = Palette { key: "", value: "" };
+    #[cfg(feature = "color")]
+    const COLORED: Palette = Palette { key: "33", value: "31" };
+}
+
+struct Paint<T>(T, &'static str);
+
+impl<T: Display> Display for Paint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.1.is_empty() {
+            self.0.fmt(f)
+        } else {
+            write!(f, "\x1b[{}m{}\x1b[0m", self.1, self.0)
+        }
+    }
+}
+
+
+/// A common flag handled by [`Arguments::handle_common_flags`].
+///
+/// [`Arguments::handle_common_flags`]: struct.Arguments.html#method.handle_common_flags
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CommonFlag {
+    /// `-h` or `--help`.
+    Help,
+    /// `-V` or `--version`.
+    Version,
+}
+
+
+/// A source of raw arguments.
+///
+/// Decouples the parser from `std::env`, so arguments can come from anywhere,
+/// like UEFI load options, an RTOS shell or a test harness.
+/// See [`Arguments::from_source`].
+///
+/// Implemented for `std::env::ArgsOs`, which skips the executable path,
+/// and for vectors, which are used as is.
+/// Uses `String` instead of `OsString` when the `utf8-only` feature is enabled.
+///
+/// [`Arguments::from_source`]: struct.Arguments.html#method.from_source
+pub trait ArgSource {
+    /// Returns arguments without the executable path.
+    fn into_args(self) -> Vec<Arg>;
+}
+
+impl<S: Into<Arg>> ArgSource for Vec<S> {
+    fn into_args(self) -> Vec<Arg> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+#[cfg(not(feature = "utf8-only"))]
+impl ArgSource for std::env::ArgsOs {
+    fn into_args(self) -> Vec<Arg> {
+        self.skip(1).collect()
+    }
+}
+
+#[cfg(feature = "utf8-only")]
+impl ArgSource for std::env::Args {
+    fn into_args(self) -> Vec<Arg> {
+        self.skip(1).collect()
+    }
+}
+
+/// A parsing event reported to an observer.
+///
+/// See [`Arguments::set_observer`].
+///
+/// [`Arguments::set_observer`]: struct.Arguments.html#method.set_observer
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Event<'a> {
+    /// Arguments were searched for a key.
+    ///
+    /// Reported for every flag and option lookup, even when nothing was found.
+    Lookup(Keys),
+
+    /// A flag was consumed.
+    #[allow(missing_docs)]
+    Flag { key: &'static str, position: usize },
+
+    /// An option was consumed.
+    ///
+    /// Non UTF-8 values are converted lossily.
+    #[allow(missing_docs)]
+    Value { key: &'static str, value: &'a str, position: usize },
+
+    /// A free-standing argument or a subcommand was consumed.
+    ///
+    /// Non UTF-8 values are converted lossily.
+    #[allow(missing_docs)]
+    Free { value: &'a str, position: usize },
+
+    /// An error occurred.
+    Error(&'a Error),
+}
+
+// Function pointers with a higher-ranked lifetime do not implement `Debug` on older compilers.
+#[derive(Clone, Copy)]
+struct Observer(fn(Event));
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+/// Separators allowed between option keys and values.
+///
+/// See [`Arguments::set_separator_policy`].
+///
+/// [`Arguments::set_separator_policy`]: struct.Arguments.html#method.set_separator_policy
+#[cfg(feature = "eq-separator")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum SeparatorPolicy {
+    /// Only `--key value`. `--key=value` is an error.
+    SpaceOnly,
+    /// Only `--key=value`. `--key value` is an error.
+    EqOnly,
+    /// Both `--key value` and `--key=value`.
+    ///
+    /// The default.
+    Both,
+}
+
+#[cfg(feature = "eq-separator")]
+impl Default for SeparatorPolicy {
+    fn default() -> Self {
+        SeparatorPolicy::Both
+    }
+}
+
+/// Quotes handling in option values.
+///
+/// `Strip` and `Verbatim` affect only `--key=value` and `-Kvalue` values,
+/// while `--key value` pairs are taken verbatim.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum QuoteMode {
+    /// Surrounding quotes are removed and escapes inside double quotes are processed.
+    ///
+    /// See [`parse_eq_value`](fn.parse_eq_value.html) for details.
+    Strip,
+
+    /// The value is taken as is, so `--pattern='"a"'` results in `'"a"'`.
+    Verbatim,
+
+    /// The same as `Strip`, but separate values, like `--key "'10'"`, are unquoted as well.
+    ///
+    /// Useful when quotes survive from scripts or Windows `cmd`.
+    /// Separate values without matching quotes are taken verbatim.
+    /// Only UTF-8 values are affected, so `*_os_str` methods still get values as is.
+    All,
+}
+
+
+// A parser with an erased value type, which stores the parsed value on its own
+// and returns a stringified error.
+type ErasedFn<'a, S> = &'a mut dyn FnMut(&S) -> Result<(), String>;
+
+// Runs a type-independent `worker` and returns the value parsed by `f`.
+//
+// Only this small adapter is instantiated for each value type,
+// while the parsing logic is compiled once.
+#[inline]
+fn parse_erased<S: ?Sized, T, E: Display>(
+    f: &mut dyn FnMut(&S) -> Result<T, E>,
+    worker: impl FnOnce(ErasedFn<S>) -> Result<(), Error>,
+) -> Result<Option<T>, Error> {
+    let mut parsed = None;
+    worker(&mut |s| {
+        parsed = Some(f(s).map_err(error_to_string)?);
+        Ok(())
+    })?;
+    Ok(parsed)
+}
+
+// A key, its value, how they were separated and the key index.
+type FoundValue<'a> = (&'static str, Cow<'a, str>, PairKind, usize);
+
+#[derive(Clone, Copy, PartialEq)]
+enum PairKind {
+    #[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
+    SingleArgument,
+    TwoArguments,
+}
+
+/// Parsing statistics.
+///
+/// See [`Arguments::stats`](struct.Arguments.html#method.stats).
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of linear searches over the remaining arguments.
+    pub scans: usize,
+    /// The number of values and arguments the parser had to copy or rewrite.
+    pub allocations: usize,
+    /// The number of removed arguments.
+    pub removed: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Stat {
+    Scan,
+    Allocation,
+    Removal,
+}
+
+// Atomics are used instead of `Cell`, so `Arguments` stays `Sync`.
+#[cfg(feature = "stats")]
+#[derive(Default, Debug)]
+struct StatsCounters {
+    scans: std::sync::atomic::AtomicUsize,
+    allocations: std::sync::atomic::AtomicUsize,
+    removed: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(feature = "stats")]
+impl StatsCounters {
+    fn get(&self) -> Stats {
+        use std::sync::atomic::Ordering::Relaxed;
+        Stats {
+            scans: self.scans.load(Relaxed),
+            allocations: self.allocations.load(Relaxed),
+            removed: self.removed.load(Relaxed),
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl Clone for StatsCounters {
+    fn clone(&self) -> Self {
+        let stats = self.get();
+        StatsCounters {
+            scans: stats.scans.into(),
+            allocations: stats.allocations.into(),
+            removed: stats.removed.into(),
+        }
+    }
+}
+
+// How a key was queried. Ordered by priority.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum UsageKind {
+    Flag,
+    Option,
+    Values,
+    Required,
+}
+
+// Queried keys and free-standing arguments, used by `Arguments::usage`.
+#[derive(Clone, Default, Debug)]
+struct Usage {
+    keys: Vec<Keys>,
+    // Parallel to `keys`.
+    kinds: Vec<UsageKind>,
+    // Placeholders of required free-standing arguments.
+    free_required: Vec<&'static str>,
+    free_optional: usize,
+}
+
+// A value returned by `Arguments::opt_value_str`.
+enum StrValue {
+    Owned(String),
+    // A range in the last retained argument.
+    Retained(std::ops::Range<usize>),
+}
+
+/// An arguments parser.
+#[derive(Clone, Debug)]
+pub struct Arguments {
+    args: Vec<Arg>,
+    // Original positions of the remaining arguments.
+    positions: Vec<usize>,
+    consumed: Vec<usize>,
+    // Keys of the consumed flags and options.
+    consumed_keys: Vec<&'static str>,
+    // Consumed arguments, which values are borrowed from.
+    retained: Vec<String>,
+    // A position for the next added argument.
+    next_position: usize,
+    #[cfg(feature = "dotenv")]
+    dotenv: Vec<(String, String)>,
+    trailing_var_arg: bool,
+    quote_mode: QuoteMode,
+    #[cfg(feature = "eq-separator")]
+    separator_policy: SeparatorPolicy,
+    observer: Option<Observer>,
+    usage: Usage,
+    strict_values: bool,
+    interpolate_env: bool,
+    #[cfg(feature = "stats")]
+    stats: StatsCounters,
+}
+
+impl Arguments {
+    /// Creates a parser from a vector of arguments.
+    ///
+    /// The executable path **must** be removed.
+    ///
+    /// This can be used for supporting `--` arguments to forward to another program.
+    /// See `examples/dash_dash.rs` for an example.
+    ///
+    /// Accepts `Vec<String>` when the `utf8-only` feature is enabled.
+    pub fn from_vec(args: Vec<Arg>) -> Self {
+        Arguments {
+            positions: (0..args.len()).collect(),
+            consumed: Vec::new(),
+            consumed_keys: Vec::new(),
+            retained: Vec::new(),
+            next_position: args.len(),
+            #[cfg(feature = "dotenv")]
+            dotenv: Vec::new(),
+            args,
+            trailing_var_arg: false,
+            quote_mode: QuoteMode::Strip,
+            #[cfg(feature = "eq-separator")]
+            separator_policy: SeparatorPolicy::Both,
+            observer: None,
+            usage: Usage::default(),
+            strict_values: false,
+            interpolate_env: false,
+            #[cfg(feature = "stats")]
+            stats: StatsCounters::default(),
+        }
+    }
+
+    /// Creates a parser from any iterator of arguments.
+    ///
+    /// The executable path **must** be removed.
+    ///
+    /// Handy in tests: `Arguments::from_iter(["-w", "10"])`.
+    /// The same as `collect()`, but doesn't require importing `FromIterator`.
+    pub fn from_iter<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Arg>,
+    {
+        Arguments::from_vec(args.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a parser from [`env::args_os`].
+    ///
+    /// The executable path will be removed.
+    ///
+    /// When the `utf8-only` feature is enabled, [`env::args`] is used instead,
+    /// which panics on a non UTF-8 argument.
+    ///
+    /// [`env::args_os`]: https://doc.rust-lang.org/stable/std/env/fn.args_os.html
+    /// [`env::args`]: https://doc.rust-lang.org/stable/std/env/fn.args.html
+    pub fn from_env() -> Self {
+        // Skip the executable path instead of removing it afterwards,
+        // which shifts all arguments and panics on an empty argv.
+        #[cfg(not(feature = "utf8-only"))]
+        {
+            Arguments::from_source(std::env::args_os())
+        }
+        #[cfg(feature = "utf8-only")]
+        {
+            Arguments::from_source(std::env::args())
+        }
+    }
+
+    /// Creates a parser for a cargo external subcommand, like `cargo-foo`.
+    ///
+    /// When running `cargo foo --bar`, cargo executes `cargo-foo foo --bar`,
+    /// so the subcommand name is duplicated. This method removes the executable path
+    /// and, when invoked through cargo, the redundant `name` token as well.
+    /// Invocations are detected via the `CARGO` environment variable, which cargo sets
+    /// for external subcommands, so running `cargo-foo foo` directly keeps `foo` untouched.
+    ///
+    /// ```no_run
+    /// // cargo-foo/src/main.rs
+    /// let mut args = pico_args::Arguments::from_env_cargo_subcommand("foo");
+    /// let verbose = args.contains("--verbose");
+    /// ```
+    pub fn from_env_cargo_subcommand(name: &str) -> Self {
+        #[cfg(not(feature = "utf8-only"))]
+        let mut args: Vec<_> = std::env::args_os().skip(1).collect();
+        #[cfg(feature = "utf8-only")]
+        let mut args: Vec<_> = std::env::args().skip(1).collect();
+        if std::env::var_os("CARGO").is_some() && args.first().map_or(false, |arg| arg == name) {
+            args.remove(0);
+        }
+        Arguments::from_vec(args)
+    }
+
+    /// Creates a parser from a NUL-separated arguments block,
+    /// like `/proc/<pid>/cmdline` on Linux.
+    ///
+    /// The block must start with the executable path, which will be removed.
+    /// A trailing NUL is optional.
+    ///
+    /// On Unix, arguments are used as is. On other platforms and when
+    /// the `utf8-only` feature is enabled, invalid UTF-8 sequences are replaced.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_nul_separated(b"app\0-v\0file\0");
+    /// assert!(args.contains("-v"));
+    /// assert_eq!(args.finish(), ["file"]);
+    /// ```
+    pub fn from_nul_separated(block: &[u8]) -> Self {
+        let block = block.strip_suffix(&[0]).unwrap_or(block);
+        Arguments::from_vec(block.split(|c| *c == 0).skip(1).map(arg_from_bytes).collect())
+    }
+
+    /// Creates a parser from any source of arguments.
+    ///
+    /// ```
+    /// use pico_args::{ArgSource, Arguments};
+    ///
+    /// // Arguments that come from a firmware or an RTOS shell.
+    /// struct LoadOptions(&'static str);
+    ///
+    /// impl ArgSource for LoadOptions {
+    ///     fn into_args(self) -> Vec<std::ffi::OsString> {
+    ///         self.0.split_whitespace().map(Into::into).collect()
+    ///     }
+    /// }
+    ///
+    /// let mut args = Arguments::from_source(LoadOptions("-v image.efi"));
+    /// assert!(args.contains("-v"));
+    /// ```
+    pub fn from_source<S: ArgSource>(source: S) -> Self {
+        Arguments::from_vec(source.into_args())
+    }
+
+    /// Creates a parser from a raw Windows command line.
+    ///
+    /// Arguments are split using the [`CommandLineToArgvW`] rules.
+    /// Useful for programs that receive the whole command line as a single string,
+    /// like GUI subsystem applications.
+    ///
+    /// The command line must start with the executable path, as returned by
+    /// `GetCommandLineW`, which will be removed.
+    ///
+    /// [`CommandLineToArgvW`]: https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-commandlinetoargvw
+    pub fn from_windows_cmdline<S: AsRef<ArgStr>>(line: S) -> Self {
+        let mut args: Vec<Arg> = cmdline::split(&arg_to_utf16(line.as_ref()))
+            .into_iter()
+            .map(arg_from_utf16)
+            .collect();
+
+        if !args.is_empty() {
+            args.remove(0);
+        }
+
+        Arguments::from_vec(args)
+    }
+
+    /// Uses environment variables with a specified prefix as fallback options.
+    ///
+    /// For each of the `keys`, a `PREFIX_SOME_NAME=value` variable is converted
+    /// into a `--some-name value` pair, so env-based configuration works with
+    /// all the `value_from_*` methods. The long key is used for the variable name when set.
+    /// Variables with an empty value are converted into flags, like `--some-name`.
+    ///
+    /// A variable is used only when the key is not present in the arguments,
+    /// so the command line always takes precedence, even for multiple values parsers,
+    /// like [`values_from_str`]. Other variables with the same prefix are ignored.
+    ///
+    /// With the `dotenv` feature, variables loaded via [`load_dotenv`] are used as well,
+    /// unless the same variable is set in the environment.
+    ///
+    /// Added arguments get positions after the original ones.
+    ///
+    /// ```
+    /// std::env::set_var("DOC_APP_MAX_DEPTH", "3");
+    ///
+    /// let mut args = pico_args::Arguments::from_vec(Vec::new());
+    /// args.extend_from_env("DOC_APP_", ["--max-depth", "--verbose"]);
+    /// assert_eq!(args.value_from_str::<_, u32>("--max-depth").unwrap(), 3);
+    /// ```
+    ///
+    /// [`values_from_str`]: struct.Arguments.html#method.values_from_str
+    /// [`load_dotenv`]: struct.Arguments.html#method.load_dotenv
+    pub fn extend_from_env<I, A>(&mut self, prefix: &str, keys: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<Keys>,
+    {
+        for keys in keys {
+            self.extend_from_env_impl(prefix, keys.into());
+        }
+    }
+
+    #[inline(never)]
+    fn extend_from_env_impl(&mut self, prefix: &str, keys: Keys) {
+        if self.find_key(keys).is_some() {
+            return;
+        }
+
+        let key = if keys.second().is_empty() { keys.first() } else { keys.second() };
+        let name = format!("{}{}", prefix, key.trim_start_matches('-').to_uppercase().replace('-', "_"));
+        if let Some(value) = self.env_arg(&name) {
+            self.push_arg(arg_from_string(key.to_string()));
+            if !value.is_empty() {
+                self.push_arg(value);
+            }
+        }
+    }
+
+    fn env_arg(&self, name: &str) -> Option<Arg> {
+        #[cfg(not(feature = "utf8-only"))]
+        let value = std::env::var_os(name);
+        // Variables with non UTF-8 values are skipped.
+        #[cfg(feature = "utf8-only")]
+        let value = std::env::var(name).ok();
+
+        if value.is_some() {
+            return value;
+        }
+
+        #[cfg(feature = "dotenv")]
+        {
+            if let Some((_, value)) = self.dotenv.iter().find(|(n, _)| n == name) {
+                return Some(arg_from_string(value.clone()));
+            }
+        }
+
+        None
+    }
+
+    /// Loads variables from a `.env` file.
+    ///
+    /// The file consists of `KEY=VALUE` lines. Empty lines, `#` comments and
+    /// an `export ` prefix are allowed. Matching surrounding quotes are removed.
+    ///
+    /// Variables are not added to the process environment,
+    /// but are used by [`extend_from_env`] as a fallback, so it must be called afterwards.
+    ///
+    /// # Errors
+    ///
+    /// - When the file cannot be read.
+    /// - When a line is not a `KEY=VALUE` pair. The error kind is `InvalidData` then.
+    ///
+    /// [`extend_from_env`]: struct.Arguments.html#method.extend_from_env
+    #[cfg(feature = "dotenv")]
+    pub fn load_dotenv<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        self.dotenv.extend(dotenv::parse(&text)?);
+        Ok(())
+    }
+
+    /// Appends an argument.
+    ///
+    /// Useful for arguments that come from elsewhere, like expanded aliases.
+    /// The argument is parsed as if it was passed at the end of the command line,
+    /// and its position follows the last original one.
+    pub fn push<S: Into<Arg>>(&mut self, arg: S) {
+        self.push_arg(arg.into());
+    }
+
+    /// Inserts an argument at the beginning.
+    ///
+    /// Allows putting back an argument that was taken by a free-standing argument method,
+    /// like [`free_from_os_str`], when it should be parsed by a later stage instead.
+    /// The argument gets a new position, like with [`push`].
+    ///
+    /// [`free_from_os_str`]: struct.Arguments.html#method.free_from_os_str
+    /// [`push`]: struct.Arguments.html#method.push
+    pub fn push_front<S: Into<Arg>>(&mut self, arg: S) {
+        self.args.insert(0, arg.into());
+        self.positions.insert(0, self.next_position);
+        self.next_position += 1;
+    }
+
+    // Appends an argument with a new position.
+    fn push_arg(&mut self, arg: Arg) {
+        self.args.push(arg);
+        self.positions.push(self.next_position);
+        self.next_position += 1;
+    }
+
+    /// Sets a function that will be called on each parsing event.
+    ///
+    /// Useful for logging how an application was invoked.
+    /// Only the methods that consume arguments are reported.
+    ///
+    /// There is no `log` or `tracing` feature, since `pico-args` has no dependencies.
+    /// An observer can forward events to either of them instead,
+    /// like `examples/trace.rs` does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pico_args::{Arguments, Event};
+    ///
+    /// fn log(event: Event) {
+    ///     eprintln!("{:?}", event);
+    /// }
+    ///
+    /// let mut args = Arguments::from_vec(vec!["-v".into()]);
+    /// args.set_observer(log);
+    /// assert!(args.contains("-v"));
+    /// ```
+    pub fn set_observer(&mut self, observer: fn(Event)) {
+        self.observer = Some(Observer(observer));
+    }
+
+    /// Returns parsing statistics.
+    ///
+    /// Useful for measuring the parser overhead. Available only with the `stats` feature.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["-v".into()]);
+    /// assert!(args.contains("-v"));
+    /// assert_eq!(args.stats().removed, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
+    #[inline]
+    fn record(&self, _stat: Stat) {
+        #[cfg(feature = "stats")]
+        {
+            use std::sync::atomic::Ordering::Relaxed;
+            let counter = match _stat {
+                Stat::Scan => &self.stats.scans,
+                Stat::Allocation => &self.stats.allocations,
+                Stat::Removal => &self.stats.removed,
+            };
+            counter.fetch_add(1, Relaxed);
+        }
+    }
+
+    #[inline(never)]
+    fn notify(&self, event: Event) {
+        if let Some(observer) = self.observer {
+            (observer.0)(event);
+        }
+    }
+
+    // Reports an error to the observer.
+    fn fail<T>(&self, e: Error) -> Result<T, Error> {
+        self.notify(Event::Error(&e));
+        Err(e)
+    }
+
+    /// Returns all keys that were queried so far, in the order of the first query.
+    ///
+    /// Keys are recorded whether or not they were present,
+    /// which can be used for "did you mean" hints or for shell completion.
+    /// Only flags and options are recorded, since free-standing arguments do not have keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pico_args::Arguments;
+    ///
+    /// let mut args = Arguments::from_vec(vec!["--widht".into(), "10".into()]);
+    /// let _ = args.contains(["-v", "--verbose"]);
+    /// let _ = args.opt_value_from_str::<_, u32>("--width");
+    /// let keys: Vec<_> = args.used_keys().iter().map(|k| k.to_string()).collect();
+    /// assert_eq!(keys, ["-v/--verbose", "--width"]);
+    /// ```
+    pub fn used_keys(&self) -> &[Keys] {
+        &self.usage.keys
+    }
+
+    fn mark_used(&mut self, keys: Keys, kind: UsageKind) {
+        self.notify(Event::Lookup(keys));
+        self.mark_usage(keys, kind);
+    }
+
+    // Records how a key is used. A required option stays required
+    // even if it is queried as an optional one later.
+    fn mark_usage(&mut self, keys: Keys, kind: UsageKind) {
+        match self.usage.keys.iter().position(|k| *k == keys) {
+            Some(idx) => {
+                let current = &mut self.usage.kinds[idx];
+                *current = std::cmp::max(*current, kind);
+            }
+            None => {
+                self.usage.keys.push(keys);
+                self.usage.kinds.push(kind);
+            }
+        }
+    }
+
+    /// Renders a best-effort usage line from the queried keys and free-standing arguments.
+    ///
+    /// Should be called after parsing. Options queried via required methods,
+    /// like [`value_from_str`], are listed without brackets. Free-standing
+    /// arguments without names are rendered as `ARG`.
+    ///
+    /// Useful for applications without a help message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["-v".into()]);
+    /// let _ = args.contains("-v");
+    /// let _ = args.opt_value_from_str::<_, u32>("--width");
+    /// let _ = args.value_from_str::<_, String>(["-i", "--input"]);
+    /// let _ = args.free_from_str::<String>();
+    /// assert_eq!(args.usage("app"), "app [-v] [--width WIDTH] --input INPUT ARG");
+    /// ```
+    ///
+    /// [`value_from_str`]: struct.Arguments.html#method.value_from_str
+    pub fn usage(&self, name: &str) -> String {
+        let mut text = name.to_string();
+        for (keys, kind) in self.usage.keys.iter().zip(&self.usage.kinds) {
+            let key = if keys.second().is_empty() { keys.first() } else { keys.second() };
+            let placeholder = key.trim_start_matches('-').replace('-', "_").to_uppercase();
+            let item = match kind {
+                UsageKind::Flag => format!(" [{}]", key),
+                UsageKind::Option => format!(" [{} {}]", key, placeholder),
+                UsageKind::Values => format!(" [{} {}]...", key, placeholder),
+                UsageKind::Required => format!(" {} {}", key, placeholder),
+            };
+            text.push_str(&item);
+        }
+
+        for name in &self.usage.free_required {
+            text.push(' ');
+            text.push_str(name);
+        }
+
+        match self.usage.free_optional {
+            0 => {}
+            1 => text.push_str(" [ARG]"),
+            _ => text.push_str(" [ARG]..."),
+        }
+
+        text
+    }
+
+    /// Enables or disables the strict values mode.
+    ///
+    /// By default, `--output --verbose` treats `--verbose` as the `--output` value.
+    /// In the strict mode, a separate value that starts with `-` is rejected
+    /// with [`Error::FlagLikeValue`] instead, unless it is a negative number or `-` itself.
+    /// Values attached via `=` or to a short key are never rejected,
+    /// so `--output=--verbose` can still be used.
+    ///
+    /// Disabled by default.
+    ///
+    /// [`Error::FlagLikeValue`]: enum.Error.html#variant.FlagLikeValue
+    pub fn set_strict_values(&mut self, enabled: bool) {
+        self.strict_values = enabled;
+    }
+
+    // Checks a separate option value in the strict values mode.
+    fn check_value(&self, key: &'static str, value: &ArgStr) -> Result<(), Error> {
+        if self.strict_values && starts_with_dash(value) && value.len() > 1 {
+            #[cfg(not(feature = "utf8-only"))]
+            let value = value.to_string_lossy().into_owned();
+            #[cfg(feature = "utf8-only")]
+            let value = value.to_string();
+            if !is_negative_number(&value) {
+                return Err(Error::FlagLikeValue { key, value });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables the trailing-var-arg mode.
+    ///
+    /// In this mode, flags and options are searched only before the first
+    /// free-standing argument, so everything after it is left as is,
+    /// even if it starts with `-`. Like in `ssh host -v`, where `-v`
+    /// belongs to the remote command.
+    ///
+    /// An argument is treated as free-standing when it doesn't start with `-`,
+    /// or is `-` or `--`. Which means that option values are indistinguishable
+    /// from free arguments, therefore options with values must be parsed
+    /// before flags. Values themselves are allowed to be past the boundary.
+    ///
+    /// Disabled by default.
+    pub fn set_trailing_var_arg(&mut self, enabled: bool) {
+        self.trailing_var_arg = enabled;
+    }
+
+    /// Sets how quotes in option values are handled.
+    ///
+    /// [`QuoteMode::Strip`] by default.
+    ///
+    /// ```
+    /// use pico_args::{Arguments, QuoteMode};
+    ///
+    /// let mut args = Arguments::from_vec(vec!["--width".into(), "'10'".into()]);
+    /// args.set_quote_mode(QuoteMode::All);
+    /// assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+    /// ```
+    ///
+    /// [`QuoteMode::Strip`]: enum.QuoteMode.html#variant.Strip
+    pub fn set_quote_mode(&mut self, mode: QuoteMode) {
+        self.quote_mode = mode;
+    }
+
+    // Strips quotes from a separate value in the `QuoteMode::All` mode.
+    // A changed value is always owned, so it can be told apart from the argument.
+    fn unquote_separate<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if self.quote_mode == QuoteMode::All {
+            if let Some(unquoted) = parse_eq_value(value) {
+                if unquoted.len() != value.len() {
+                    self.record(Stat::Allocation);
+                    return Cow::Owned(unquoted.into_owned());
+                }
+            }
+        }
+
+        Cow::Borrowed(value)
+    }
+
+    /// Enables or disables environment variables interpolation in option values.
+    ///
+    /// When enabled, `${NAME}` in a value is replaced with the value of the `NAME`
+    /// environment variable before parsing. On Windows, `%NAME%` is supported as well.
+    /// `$$` (and `%%` on Windows) produces a literal `$` (`%`).
+    /// Variables loaded via [`load_dotenv`] are used when not set in the environment.
+    ///
+    /// Applies only to values parsed as UTF-8 strings. Quotes are stripped first.
+    /// Free-standing arguments are left as is.
+    ///
+    /// Useful for tools run from schedulers, where argv is a template.
+    ///
+    /// Disabled by default.
+    ///
+    /// ```
+    /// std::env::set_var("PICO_ARGS_DOC_ROOT", "/srv");
+    ///
+    /// let mut args = pico_args::Arguments::from_vec(
+    ///     vec!["--out".into(), "${PICO_ARGS_DOC_ROOT}/out".into()]
+    /// );
+    /// args.set_env_interpolation(true);
+    /// assert_eq!(args.value_from_str::<_, String>("--out").unwrap(), "/srv/out");
+    /// ```
+    ///
+    /// An undefined variable results in [`Error::UndefinedVariable`].
+    ///
+    /// [`load_dotenv`]: struct.Arguments.html#method.load_dotenv
+    /// [`Error::UndefinedVariable`]: enum.Error.html#variant.UndefinedVariable
+    pub fn set_env_interpolation(&mut self, enabled: bool) {
+        self.interpolate_env = enabled;
+    }
+
+    // Expands environment variables in a value, when enabled.
+    // A changed value is always owned, so it can be told apart from the argument.
+    fn interpolate<'a>(&self, key: &'static str, value: Cow<'a, str>) -> Result<Cow<'a, str>, Error> {
+        if !self.interpolate_env {
+            return Ok(value);
+        }
+
+        match expand_vars(&value, cfg!(windows), &|name| self.env_var(name)) {
+            Ok(Some(expanded)) => {
+                self.record(Stat::Allocation);
+                Ok(Cow::Owned(expanded))
+            }
+            Ok(None) => Ok(value),
+            Err(name) => Err(Error::UndefinedVariable { key, name }),
+        }
+    }
+
+    fn env_var(&self, name: &str) -> Option<String> {
+        if let Ok(value) = std::env::var(name) {
+            return Some(value);
+        }
+
+        #[cfg(feature = "dotenv")]
+        {
+            if let Some((_, value)) = self.dotenv.iter().find(|(n, _)| n == name) {
+                return Some(value.clone());
+            }
+        }
+
+        None
+    }
+
+    /// Sets which separators between option keys and values are allowed.
+    ///
+    /// Allows binaries in a workspace, where the `eq-separator` feature is unified,
+    /// to keep different syntaxes. An option that uses a disallowed separator
+    /// results in [`Error::OptionWithoutAValue`].
+    ///
+    /// Doesn't affect `-Kvalue` pairs of the `short-space-opt` feature.
+    ///
+    /// [`SeparatorPolicy::Both`] by default.
+    ///
+    /// ```
+    /// use pico_args::{Arguments, SeparatorPolicy};
+    ///
+    /// let mut args = Arguments::from_vec(vec!["--width=10".into()]);
+    /// args.set_separator_policy(SeparatorPolicy::SpaceOnly);
+    /// assert!(args.value_from_str::<_, u32>("--width").is_err());
+    /// ```
+    ///
+    /// [`Error::OptionWithoutAValue`]: enum.Error.html#variant.OptionWithoutAValue
+    /// [`SeparatorPolicy::Both`]: enum.SeparatorPolicy.html#variant.Both
+    #[cfg(feature = "eq-separator")]
+    pub fn set_separator_policy(&mut self, policy: SeparatorPolicy) {
+        self.separator_policy = policy;
+    }
+
+    /// Parses the name of the subcommand, that is, the first positional argument.
+    ///
+    /// Returns `None` when subcommand starts with `-` or when there are no arguments left.
+    ///
+    /// # Errors
+    ///
+    /// - When arguments is not a UTF-8 string.
+    pub fn subcommand(&mut self) -> Result<Option<String>, Error> {
+        if self.args.is_empty() {
+            return Ok(None);
+        }
+
+        if starts_with_dash(&self.args[0]) {
+            return Ok(None);
+        }
+
+        match arg_to_str(&self.args[0]) {
+            Some(s) => self.notify(Event::Free { value: s, position: self.positions[0] }),
+            None => return self.fail(Error::NonUtf8Argument),
+        }
+
+        arg_into_string(self.take(0)).map(Some)
+    }
+
+    /// Parses up to `max_depth` nested subcommands, like `remote add` in `git remote add origin`.
+    ///
+    /// Stops at the first argument that starts with `-` or when there are no arguments left.
+    /// Since subcommands are indistinguishable from free-standing arguments,
+    /// `max_depth` must not exceed the deepest subcommand level.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(
+    ///     vec!["remote".into(), "add".into(), "origin".into()]
+    /// );
+    /// assert_eq!(args.subcommand_path(2).unwrap(), ["remote", "add"]);
+    /// assert_eq!(args.free_from_str::<String>().unwrap(), "origin");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When a subcommand is not a UTF-8 string.
+    pub fn subcommand_path(&mut self, max_depth: usize) -> Result<Vec<String>, Error> {
+        let mut path = Vec::new();
+        while path.len() < max_depth {
+            match self.subcommand()? {
+                Some(name) => path.push(name),
+                None => break,
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Returns the subcommand as is.
+    ///
+    /// The same as [`subcommand`], but supports non UTF-8 subcommands,
+    /// like arbitrary file names.
+    ///
+    /// [`subcommand`]: struct.Arguments.html#method.subcommand
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn subcommand_os(&mut self) -> Option<OsString> {
+        match self.args.first() {
+            Some(v) if !starts_with_dash(v) => {
+                self.notify(Event::Free { value: &v.to_string_lossy(), position: self.positions[0] });
+                Some(self.take(0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the subcommand, replacing invalid UTF-8 sequences.
+    ///
+    /// The same as [`subcommand_os`], but converts the result
+    /// using [`OsStr::to_string_lossy`].
+    ///
+    /// [`subcommand_os`]: struct.Arguments.html#method.subcommand_os
+    /// [`OsStr::to_string_lossy`]: https://doc.rust-lang.org/stable/std/ffi/struct.OsStr.html#method.to_string_lossy
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn subcommand_lossy(&mut self) -> Option<String> {
+        self.subcommand_os().map(|s| s.to_string_lossy().into_owned())
+    }
+
+    /// Parses the subcommand using `FromStr` trait.
+    ///
+    /// This is a shorthand for `subcommand_from_fn(FromStr::from_str)`
+    pub fn subcommand_from_str<T>(&mut self) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.subcommand_from_fn(FromStr::from_str)
+    }
+
+    /// Parses the subcommand using a specified function.
+    ///
+    /// Useful for parsing a subcommand into a user-defined enum.
+    ///
+    /// The same as [`subcommand`], but the subcommand is removed
+    /// only when parsing was successful.
+    ///
+    /// # Errors
+    ///
+    /// - When arguments is not a UTF-8 string.
+    /// - When subcommand parsing failed.
+    ///
+    /// [`subcommand`]: struct.Arguments.html#method.subcommand
+    pub fn subcommand_from_fn<T, E, F>(&mut self, mut f: F) -> Result<Option<T>, Error>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.subcommand_from_fn_impl(&mut f)
+    }
+
+    #[inline]
+    fn subcommand_from_fn_impl<T, E: Display>(
+        &mut self,
+        f: &mut dyn FnMut(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.subcommand_erased(f))
+    }
+
+    #[inline(never)]
+    fn subcommand_erased(&mut self, f: ErasedFn<str>) -> Result<(), Error> {
+        let value = match self.args.first() {
+            Some(v) if starts_with_dash(v) => return Ok(()),
+            Some(v) => match arg_to_str(v) {
+                Some(v) => v,
+                None => return self.fail(Error::NonUtf8Argument),
+            },
+            None => return Ok(()),
+        };
+
+        match f(value) {
+            Ok(()) => {
+                self.notify(Event::Free { value, position: self.positions[0] });
+                self.take(0);
+                Ok(())
+            }
+            Err(cause) => self.fail(Error::Utf8ArgumentParsingFailed {
+                value: value.to_string(),
+                cause,
+            }),
+        }
+    }
+
+    /// Parses the name of the subcommand and checks that it is a known one.
+    ///
+    /// The same as [`subcommand`], but returns an error when the subcommand
+    /// is not in the `known` list. The error contains the closest known subcommand,
+    /// so one can print "unknown subcommand 'craete', did you mean 'create'?".
+    ///
+    /// An unknown subcommand is not removed.
+    ///
+    /// # Errors
+    ///
+    /// - When arguments is not a UTF-8 string.
+    /// - When subcommand is not in the `known` list.
+    ///
+    /// [`subcommand`]: struct.Arguments.html#method.subcommand
+    pub fn subcommand_among(&mut self, known: &[&'static str]) -> Result<Option<String>, Error> {
+        if let Some(s) = self.args.first().and_then(|s| arg_to_str(s)) {
+            if !s.starts_with('-') && !known.contains(&s) {
+                return self.fail(Error::UnknownSubcommand {
+                    name: s.to_string(),
+                    suggestion: closest_match(s, known),
+                });
+            }
+        }
+
+        self.subcommand()
+    }
+
+    /// Handles `-h/--help` and `-V/--version` flags.
+    ///
+    /// Should be called before anything else, since those flags have a higher priority.
+    ///
+    /// When one of them is present, prints a corresponding text to stdout as is
+    /// and returns which flag was handled. Help takes precedence over version.
+    /// It's up to the caller to exit afterwards.
+    pub fn handle_common_flags(&mut self, help: &str, version: &str) -> Option<CommonFlag> {
+        if self.contains(["-h", "--help"]) {
+            print!("{}", help);
+            Some(CommonFlag::Help)
+        } else if self.contains(["-V", "--version"]) {
+            print!("{}", version);
+            Some(CommonFlag::Version)
+        } else {
+            None
+        }
+    }
+
+    /// Checks that arguments contain a specified flag.
+    ///
+    /// Searches through all arguments, not only the first/next one.
+    ///
+    /// Calling this method "consumes" the flag: if a flag is present `n`
+    /// times then the first `n` calls to `contains` for that flag will
+    /// return `true`, and subsequent calls will return `false`.
+    ///
+    /// When the "combined-flags" feature is used, repeated letters count
+    /// as repeated flags: `-vvv` is treated the same as `-v -v -v`.
+    pub fn contains<A: Into<Keys>>(&mut self, keys: A) -> bool {
+        self.contains_impl(keys.into())
+    }
+
+    #[inline(never)]
+    fn contains_impl(&mut self, keys: Keys) -> bool {
+        self.mark_used(keys, UsageKind::Flag);
+
+        if let Some((idx, key)) = self.index_of(keys) {
+            self.notify(Event::Flag { key, position: self.positions[idx] });
+            self.consume_key(key);
+            self.take(idx);
+            true
+        } else {
+            #[cfg(feature = "combined-flags")]
+            // Combined flags only work if the short flag is a single character
+            {
+                if keys.first().len() == 2 {
+                    let short_flag = &keys.first()[1..2];
+                    let end = self.search_end();
+                    for (n, item) in self.args[..end].iter().enumerate() {
+                        if let Some(s) = arg_to_str(item) {
+                            if s.starts_with('-') && !s.starts_with("--") && s.contains(short_flag) {
+                                self.notify(Event::Flag { key: keys.first(), position: self.positions[n] });
+                                if s.len() == 2 {
+                                    // last flag
+                                    self.take(n);
+                                } else {
+                                    self.record(Stat::Allocation);
+                                    self.args[n] = arg_from_string(s.replacen(short_flag, "", 1));
+                                }
+                                self.consume_key(keys.first());
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        }
+    }
+
+    /// Checks a tri-state toggle flag.
+    ///
+    /// Follows the shells convention, where `-x` enables a feature and `+x` disables it.
+    /// The `+` form is derived from the keys by replacing the leading `-`,
+    /// so `["-x", "--xtrace"]` also matches `+x` and `+-xtrace`.
+    ///
+    /// Consumes all occurrences and the last one wins.
+    /// Returns `None` when the flag is not present.
+    pub fn toggle<A: Into<Keys>>(&mut self, keys: A) -> Option<bool> {
+        self.toggle_impl(keys.into())
+    }
+
+    #[inline(never)]
+    fn toggle_impl(&mut self, keys: Keys) -> Option<bool> {
+        self.mark_used(keys, UsageKind::Flag);
+
+        let mut state = None;
+        let mut idx = 0;
+        while idx < self.search_end() {
+            let value = match arg_to_str(&self.args[idx]) {
+                Some(s) => keys.iter().find_map(|key| {
+                    if s == key {
+                        Some((key, true))
+                    } else if s.starts_with('+') && s[1..] == key[1..] {
+                        Some((key, false))
+                    } else {
+                        None
+                    }
+                }),
+                None => None,
+            };
+
+            match value {
+                Some((key, value)) => {
+                    state = Some(value);
+                    self.consume_key(key);
+                    self.take(idx);
+                }
+                None => idx += 1,
+            }
+        }
+
+        state
+    }
+
+    /// Parses a numeric short option, like `-5` in `head -5`.
+    ///
+    /// Takes the first argument that consists of `-` followed only by digits.
+    /// Returns `None` when there is no such argument.
+    ///
+    /// Negative number values, like `--offset -5`, must be parsed before this method.
+    ///
+    /// # Errors
+    ///
+    /// - When the number doesn't fit into `u64`.
+    pub fn numeric_option(&mut self) -> Result<Option<u64>, Error> {
+        self.record(Stat::Scan);
+        let end = self.search_end();
+        let idx = match self.args[..end].iter().position(|arg| {
+            match arg_to_str(arg).and_then(|s| s.strip_prefix('-')) {
+                Some(digits) => !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit()),
+                None => false,
+            }
+        }) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+
+        let value = arg_to_str(&self.args[idx]).unwrap_or_default();
+        match value[1..].parse() {
+            Ok(number) => {
+                self.notify(Event::Free { value, position: self.positions[idx] });
+                self.take(idx);
+                Ok(Some(number))
+            }
+            Err(e) => self.fail(Error::Utf8ArgumentParsingFailed {
+                value: value.to_string(),
+                cause: error_to_string(e),
+            }),
+        }
+    }
+
+    /// Parses a key-value pair using `FromStr` trait.
+    ///
+    /// This is a shorthand for `value_from_fn("--key", FromStr::from_str)`
+    pub fn value_from_str<A, T>(&mut self, keys: A) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.value_from_fn(keys, FromStr::from_str)
+    }
+
+    /// Parses a key-value pair using a specified function.
+    ///
+    /// Searches through all argument, not only the first/next one.
+    ///
+    /// When a key-value pair is separated by a space, the algorithm
+    /// will threat the next argument after the key as a value,
+    /// even if it has a `-/--` prefix.
+    /// So a key-value pair like `--key --value` is not an error.
+    ///
+    /// The function can be a closure, which is useful when parsing
+    /// depends on some context, like a base directory.
+    ///
+    /// Must be used only once for each option.
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When key or value is not a UTF-8 string. Use [`value_from_os_str`] instead.
+    /// - When value parsing failed.
+    /// - When key-value pair is separated not by space or `=`.
+    ///
+    /// [`value_from_os_str`]: struct.Arguments.html#method.value_from_os_str
+    pub fn value_from_fn<A, T, E, F>(&mut self, keys: A, f: F) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
+        match self.opt_value_from_fn(keys, f) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses an optional key-value pair using `FromStr` trait.
+    ///
+    /// This is a shorthand for `opt_value_from_fn("--key", FromStr::from_str)`
+    pub fn opt_value_from_str<A, T>(&mut self, keys: A) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.opt_value_from_fn(keys, FromStr::from_str)
+    }
+
+    /// Parses an optional key-value pair using a specified function.
+    ///
+    /// The same as [`value_from_fn`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`value_from_fn`]: struct.Arguments.html#method.value_from_fn
+    pub fn opt_value_from_fn<A, T, E, F>(&mut self, keys: A, mut f: F) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.opt_value_from_fn_impl(keys.into(), &mut f)
+    }
+
+    /// Parses a key-value pair using `FromStr` trait and checks that it is in a range.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["--jobs".into(), "100".into()]);
+    /// let e = args.value_from_str_in_range::<_, _, u32>("--jobs", 1..=64).unwrap_err();
+    /// assert_eq!(e.to_string(), "failed to parse '--jobs 100': expected a value in 1..=64");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When value parsing failed.
+    /// - When value is not in the range.
+    pub fn value_from_str_in_range<A, R, T>(&mut self, keys: A, range: R) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        R: RangeBounds<T>,
+        T: FromStr + PartialOrd + Display,
+        <T as FromStr>::Err: Display,
+    {
+        let keys = keys.into();
+        match self.opt_value_from_str_in_range(keys, range) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses an optional key-value pair using `FromStr` trait and checks that it is in a range.
+    ///
+    /// The same as [`value_from_str_in_range`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`value_from_str_in_range`]: struct.Arguments.html#method.value_from_str_in_range
+    pub fn opt_value_from_str_in_range<A, R, T>(
+        &mut self,
+        keys: A,
+        range: R,
+    ) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        R: RangeBounds<T>,
+        T: FromStr + PartialOrd + Display,
+        <T as FromStr>::Err: Display,
+    {
+        self.opt_value_from_fn(keys, |s| {
+            let value = T::from_str(s).map_err(|e| e.to_string())?;
+            if range.contains(&value) {
+                Ok(value)
+            } else {
+                Err(format!("expected a value in {}", range_to_string(&range)))
+            }
+        })
+    }
+
+    /// Parses a key-value pair using a specified function, preserving its error.
+    ///
+    /// The same as [`value_from_fn`], but the function error is returned as is,
+    /// via [`TryError::Value`], instead of being converted into a string.
+    ///
+    /// [`value_from_fn`]: struct.Arguments.html#method.value_from_fn
+    /// [`TryError::Value`]: enum.TryError.html#variant.Value
+    pub fn try_value_from_fn<A, T, E, F>(&mut self, keys: A, f: F) -> Result<T, TryError<E>>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
+        match self.try_opt_value_from_fn(keys, f) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => self.fail(Error::MissingOption(keys)).map_err(TryError::Args),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses an optional key-value pair using a specified function, preserving its error.
+    ///
+    /// The same as [`try_value_from_fn`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`try_value_from_fn`]: struct.Arguments.html#method.try_value_from_fn
+    pub fn try_opt_value_from_fn<A, T, E, F>(
+        &mut self,
+        keys: A,
+        mut f: F,
+    ) -> Result<Option<T>, TryError<E>>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        // Reuse the common logic and stash the original error.
+        // Its message is still used as a cause, so the observer sees the real error.
+        let mut error = None;
+        let result = self.opt_value_from_fn_impl(keys.into(), &mut |s| {
+            f(s).map_err(|e| {
+                let cause = e.to_string();
+                error = Some(e);
+                cause
+            })
+        });
+
+        match (result, error) {
+            (Ok(v), _) => Ok(v),
+            (Err(_), Some(e)) => Err(TryError::Value(e)),
+            (Err(e), None) => Err(TryError::Args(e)),
+        }
+    }
+
+    /// Parses an optional key-value pair using a specified function without allocations
+    /// on error paths.
+    ///
+    /// The same as [`opt_value_from_fn`], but returns a [`ProbeError`], which references
+    /// the offending argument by its original position instead of copying it.
+    /// The function error is discarded. Useful for probing options speculatively.
+    ///
+    /// Errors are not reported to the observer.
+    /// Arguments are left intact on error, so the option can be parsed again.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["--jobs".into(), "auto".into()]);
+    /// let e = args.probe_value_from_fn("--jobs", |s| s.parse::<u32>()).unwrap_err();
+    /// assert_eq!(e.key(), Some("--jobs"));
+    /// assert_eq!(e.position(), Some(1));
+    /// assert_eq!(args.value_str("--jobs").unwrap(), "auto");
+    /// ```
+    ///
+    /// [`opt_value_from_fn`]: struct.Arguments.html#method.opt_value_from_fn
+    /// [`ProbeError`]: struct.ProbeError.html
+    pub fn probe_value_from_fn<A, T, E, F>(&mut self, keys: A, mut f: F) -> Result<Option<T>, ProbeError>
+    where
+        A: Into<Keys>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let mut parsed = None;
+        self.probe_value_impl(keys.into(), &mut |s| match f(s) {
+            Ok(v) => {
+                parsed = Some(v);
+                true
+            }
+            Err(_) => false,
+        })?;
+        Ok(parsed)
+    }
+
+    #[inline(never)]
+    fn probe_value_impl(&mut self, keys: Keys, f: &mut dyn FnMut(&str) -> bool) -> Result<(), ProbeError> {
+        self.mark_used(keys, UsageKind::Option);
+
+        let (key, value, kind, idx) = match self.find_value(keys) {
+            Ok(Some(v)) => v,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                let position = self.index_of(keys).map(|(idx, _)| self.positions[idx]);
+                return Err(ProbeError { kind: e.kind(), key: e.key(), position });
+            }
+        };
+
+        let value_idx = if kind == PairKind::TwoArguments { idx + 1 } else { idx };
+        if !f(&value) {
+            return Err(ProbeError {
+                kind: ErrorKind::OptionParsingFailed,
+                key: Some(key),
+                position: Some(self.positions[value_idx]),
+            });
+        }
+
+        self.notify(Event::Value { key, value: &value, position: self.positions[idx] });
+
+        // Release the borrow, since the value may point into the arguments.
+        drop(value);
+
+        self.consume_key(key);
+        self.take(idx);
+        if kind == PairKind::TwoArguments {
+            self.take(idx);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn opt_value_from_fn_impl<T, E: Display>(
+        &mut self,
+        keys: Keys,
+        f: &mut dyn FnMut(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.opt_value_erased(keys, f))
+    }
+
+    #[inline(never)]
+    fn opt_value_erased(&mut self, keys: Keys, f: ErasedFn<str>) -> Result<(), Error> {
+        self.mark_used(keys, UsageKind::Option);
+
+        let (key, value, kind, idx) = match self.find_value(keys) {
+            Ok(Some(v)) => v,
+            Ok(None) => return Ok(()),
+            Err(e) => return self.fail(e),
+        };
+
+        match f(&value) {
+            Ok(()) => {
+                self.notify(Event::Value { key, value: &value, position: self.positions[idx] });
+
+                // Release the borrow, since the value may point into the arguments.
+                drop(value);
+
+                // Remove only when all checks are passed.
+                self.consume_key(key);
+                self.take(idx);
+                if kind == PairKind::TwoArguments {
+                    self.take(idx);
+                }
+
+                Ok(())
+            }
+            Err(cause) => {
+                let e = Error::OptionParsingFailed {
+                    key,
+                    value: value.into_owned(),
+                    cause,
+                };
+                self.fail(e)
+            }
+        }
+    }
+
+    // The whole logic must be type-independent to prevent monomorphization.
+    #[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
+    #[inline(never)]
+    fn find_value(
+        &self,
+        keys: Keys,
+    ) -> Result<Option<FoundValue<'_>>, Error> {
+        if let Some((idx, key)) = self.index_of(keys) {
+            // Parse a `--key value` pair.
+
+            #[cfg(feature = "eq-separator")]
+            {
+                if self.separator_policy == SeparatorPolicy::EqOnly {
+                    return Err(Error::OptionWithoutAValue(key));
+                }
+            }
+
+            let value = match self.args.get(idx + 1) {
+                Some(v) => v,
+                None => return Err(Error::OptionWithoutAValue(key)),
+            };
+
+            self.check_value(key, value)?;
+            let value = self.unquote_separate(os_to_str(value)?);
+            let value = self.interpolate(key, value)?;
+            Ok(Some((key, value, PairKind::TwoArguments, idx)))
+        } else if let Some((idx, key)) = self.index_of2(keys) {
+            // Parse a `--key=value` or `-Kvalue` pair.
+
+            let value = &self.args[idx];
+
+            // Only UTF-8 strings are supported in this method.
+            let value = os_to_str(value)?;
+
+            let start = key.len();
+            let start = if value.as_bytes().get(start) == Some(&b'=') {
+                #[cfg(feature = "eq-separator")]
+                {
+                    if self.separator_policy == SeparatorPolicy::SpaceOnly {
+                        return Err(Error::OptionWithoutAValue(key));
+                    }
+                    start + 1
+                }
+                #[cfg(not(feature = "eq-separator"))]
+                return Err(Error::OptionWithoutAValue(key));
+            } else {
+                // Key must be followed by `=` if not `short-space-opt`
+                #[cfg(not(feature = "short-space-opt"))]
+                return Err(Error::OptionWithoutAValue(key));
+                #[cfg(feature = "short-space-opt")]
+                start
+            };
+
+            // Extract `value` from `--key="value"`.
+            let value = match self.quote_mode {
+                QuoteMode::Strip | QuoteMode::All => parse_eq_value(&value[start..]),
+                QuoteMode::Verbatim if start < value.len() => Some(Cow::Borrowed(&value[start..])),
+                QuoteMode::Verbatim => None,
+            };
+            let value = match value {
+                Some(v) => v,
+                None => return Err(Error::OptionWithoutAValue(key)),
+            };
+            if let Cow::Owned(_) = value {
+                self.record(Stat::Allocation);
+            }
+            let value = self.interpolate(key, value)?;
+
+            Ok(Some((key, value, PairKind::SingleArgument, idx)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // The whole logic must be type-independent to prevent monomorphization.
+    #[cfg(not(any(feature = "eq-separator", feature = "short-space-opt")))]
+    #[inline(never)]
+    fn find_value(
+        &self,
+        keys: Keys,
+    ) -> Result<Option<FoundValue<'_>>, Error> {
+        if let Some((idx, key)) = self.index_of(keys) {
+            // Parse a `--key value` pair.
+
+            let value = match self.args.get(idx + 1) {
+                Some(v) => v,
+                None => return Err(Error::OptionWithoutAValue(key)),
+            };
+
+            self.check_value(key, value)?;
+            let value = self.unquote_separate(os_to_str(value)?);
+            let value = self.interpolate(key, value)?;
+            Ok(Some((key, value, PairKind::TwoArguments, idx)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parses a key-value pair and takes all arguments after it as is.
+    ///
+    /// Useful for interpreter-like applications, like `app -e 'script' -x arg`,
+    /// where arguments after the script belong to it. A leading `--` is removed from them.
+    ///
+    /// Only arguments after the first occurrence of the key are taken.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(
+    ///     vec!["-v".into(), "-e".into(), "print".into(), "--".into(), "-v".into()]
+    /// );
+    /// let (script, rest): (String, _) = args.value_from_str_then_rest("-e").unwrap();
+    /// assert_eq!(script, "print");
+    /// assert_eq!(rest, ["-v"]);
+    /// assert!(args.contains("-v"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When key or value is not a UTF-8 string.
+    /// - When value parsing failed.
+    pub fn value_from_str_then_rest<A, T>(&mut self, keys: A) -> Result<(T, Vec<Arg>), Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let keys = keys.into();
+        let idx = match self.find_value(keys) {
+            Ok(Some((_, _, _, idx))) => idx,
+            Ok(None) => return self.fail(Error::MissingOption(keys)),
+            Err(e) => return self.fail(e),
+        };
+
+        // The key and the value are removed, so the rest starts at the key index.
+        let value = self.value_from_str(keys)?;
+        Ok((value, self.take_rest(idx)))
+    }
+
+    // Removes all arguments starting from the index, except a leading `--`.
+    fn take_rest(&mut self, idx: usize) -> Vec<Arg> {
+        let mut rest = self.args.split_off(idx);
+        self.consumed.extend(self.positions.drain(idx..));
+        if rest.first().and_then(|arg| arg_to_str(arg)) == Some("--") {
+            rest.remove(0);
+        }
+
+        rest
+    }
+
+    /// Returns a key-value pair value without copying it.
+    ///
+    /// Unlike `value_from_str::<String>`, doesn't allocate, unless the value
+    /// had to be unquoted or interpolated. A `--key value` argument is moved out
+    /// of the parser and a `--key=value` one is kept alive, so the value can be borrowed.
+    /// Handy when a value only needs to be compared against some constants.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["--mode".into(), "fast".into()]);
+    /// assert_eq!(args.value_str("--mode").unwrap(), "fast");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When key or value is not a UTF-8 string.
+    /// - When key-value pair is separated not by space or `=`.
+    pub fn value_str<A: Into<Keys>>(&mut self, keys: A) -> Result<Cow<'_, str>, Error> {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
+        match self.opt_value_str_impl(keys) {
+            Ok(Some(v)) => Ok(self.str_value(v)),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns an optional key-value pair value without copying it.
+    ///
+    /// The same as [`value_str`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`value_str`]: struct.Arguments.html#method.value_str
+    pub fn opt_value_str<A: Into<Keys>>(&mut self, keys: A) -> Result<Option<Cow<'_, str>>, Error> {
+        let value = self.opt_value_str_impl(keys.into())?;
+        Ok(value.map(move |v| self.str_value(v)))
+    }
 
-    // The whole logic must be type-independent to prevent monomorphization.
-    #[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
     #[inline(never)]
-    fn find_value(
-        &mut self,
-        keys: Keys,
-    ) -> Result<Option<(&str, PairKind, usize)>, Error> {
-        if let Some((idx, key)) = self.index_of(keys) {
-            // Parse a `--key value` pair.
+    fn opt_value_str_impl(&mut self, keys: Keys) -> Result<Option<StrValue>, Error> {
+        self.mark_used(keys, UsageKind::Option);
 
-            let value = match self.0.get(idx + 1) {
-                Some(v) => v,
-                None => return Err(Error::OptionWithoutAValue(key)),
-            };
+        let (idx, kind, value) = match self.find_value(keys) {
+            Ok(Some((key, value, kind, idx))) => {
+                self.notify(Event::Value { key, value: &value, position: self.positions[idx] });
+                // Values changed by unquoting or interpolation are already owned.
+                // Otherwise, remember where the value is inside its argument.
+                let value = match value {
+                    Cow::Owned(value) => Ok(value),
+                    Cow::Borrowed(value) => {
+                        let arg = &self.args[if kind == PairKind::TwoArguments { idx + 1 } else { idx }];
+                        let start = value.as_ptr() as usize - os_to_str(arg)?.as_ptr() as usize;
+                        Err(start..start + value.len())
+                    }
+                };
+                self.consume_key(key);
+                (idx, kind, value)
+            }
+            Ok(None) => return Ok(None),
+            Err(e) => return self.fail(e),
+        };
 
-            let value = os_to_str(value)?;
-            Ok(Some((value, PairKind::TwoArguments, idx)))
-        } else if let Some((idx, key)) = self.index_of2(keys) {
-            // Parse a `--key=value` or `-Kvalue` pair.
+        let mut arg = self.take(idx);
+        if kind == PairKind::TwoArguments {
+            arg = self.take(idx);
+        }
 
-            let value = &self.0[idx];
+        match value {
+            Ok(value) => Ok(Some(StrValue::Owned(value))),
+            Err(_) if kind == PairKind::TwoArguments => {
+                arg_into_string(arg).map(|s| Some(StrValue::Owned(s)))
+            }
+            Err(range) => {
+                self.retained.push(arg_into_string(arg)?);
+                Ok(Some(StrValue::Retained(range)))
+            }
+        }
+    }
 
-            // Only UTF-8 strings are supported in this method.
-            let value = value.to_str().ok_or_else(|| Error::NonUtf8Argument)?;
+    fn str_value(&self, value: StrValue) -> Cow<'_, str> {
+        match value {
+            StrValue::Owned(value) => Cow::Owned(value),
+            StrValue::Retained(range) => {
+                let arg = self.retained.last().map_or("", |arg| arg.as_str());
+                Cow::Borrowed(&arg[range])
+            }
+        }
+    }
 
-            let mut value_range = key.len()..value.len();
+    /// Parses multiple key-value pairs into the `Vec` using `FromStr` trait.
+    ///
+    /// This is a shorthand for `values_from_fn("--key", FromStr::from_str)`
+    pub fn values_from_str<A, T>(&mut self, keys: A) -> Result<Vec<T>, Error>
+        where
+            A: Into<Keys>,
+            T: FromStr,
+            <T as FromStr>::Err: Display,
+    {
+        self.values_from_fn(keys, FromStr::from_str)
+    }
 
-            if value.as_bytes().get(value_range.start) == Some(&b'=') {
-                #[cfg(feature = "eq-separator")]
-                {
-                    value_range.start += 1;
-                }
-                #[cfg(not(feature = "eq-separator"))]
-                return Err(Error::OptionWithoutAValue(key));
-            } else {
-                // Key must be followed by `=` if not `short-space-opt`
-                #[cfg(not(feature = "short-space-opt"))]
-                return Err(Error::OptionWithoutAValue(key));
+    /// Parses multiple key-value pairs into the `Vec` using a specified function.
+    ///
+    /// This functions can be used to parse arguments like:<br>
+    /// `--file /path1 --file /path2 --file /path3`<br>
+    /// But not `--file /path1 /path2 /path3`.
+    ///
+    /// Arguments can also be separated: `--file /path1 --some-flag --file /path2`
+    ///
+    /// This method simply executes [`opt_value_from_fn`] multiple times.
+    ///
+    /// An empty `Vec` is not an error.
+    ///
+    /// [`opt_value_from_fn`]: struct.Arguments.html#method.opt_value_from_fn
+    pub fn values_from_fn<A, T, E, F>(&mut self, keys: A, mut f: F) -> Result<Vec<T>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Values);
+
+        let mut values = Vec::new();
+        loop {
+            match self.opt_value_from_fn_impl(keys, &mut f) {
+                Ok(Some(v)) => values.push(v),
+                Ok(None) => break,
+                Err(e) => return Err(e),
             }
+        }
 
-            // Check for quoted value.
-            if let Some(c) = value.as_bytes().get(value_range.start).cloned() {
-                if c == b'"' || c == b'\'' {
-                    value_range.start += 1;
+        Ok(values)
+    }
 
-                    // A closing quote must be the same as an opening one.
-                    if ends_with(&value[value_range.start..], c) {
-                        value_range.end -= 1;
-                    } else {
-                        return Err(Error::OptionWithoutAValue(key));
+    /// Parses multiple key-value pairs with their positions using `FromStr` trait.
+    ///
+    /// This is a shorthand for `indexed_values_from_fn("--key", FromStr::from_str)`
+    pub fn indexed_values_from_str<A, T>(&mut self, keys: A) -> Result<Vec<(usize, T)>, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.indexed_values_from_fn(keys, FromStr::from_str)
+    }
+
+    /// Parses multiple key-value pairs with their positions using a specified function.
+    ///
+    /// The same as [`values_from_fn`], but each value is paired with the original
+    /// position of its key, so interleaved options like `-I a -L x -I b`
+    /// can be merged back in order. Values are sorted by position.
+    ///
+    /// See [`consumed`] for details about positions.
+    ///
+    /// [`values_from_fn`]: struct.Arguments.html#method.values_from_fn
+    /// [`consumed`]: struct.Arguments.html#method.consumed
+    pub fn indexed_values_from_fn<A, T, E, F>(
+        &mut self,
+        keys: A,
+        mut f: F,
+    ) -> Result<Vec<(usize, T)>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Values);
+
+        let mut values = Vec::new();
+        loop {
+            // A key position is always recorded first.
+            let idx = self.consumed.len();
+            match self.opt_value_from_fn_impl(keys, &mut f) {
+                Ok(Some(v)) => values.push((self.consumed[idx], v)),
+                Ok(None) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        // `--key value` pairs are found before `--key=value` ones.
+        values.sort_by_key(|(idx, _)| *idx);
+        Ok(values)
+    }
+
+    /// Parses multiple key-value pairs with a limited number of occurrences using `FromStr` trait.
+    ///
+    /// This is a shorthand for `values_from_fn_bounded("--key", range, FromStr::from_str)`
+    pub fn values_from_str_bounded<A, R, T>(&mut self, keys: A, range: R) -> Result<Vec<T>, Error>
+    where
+        A: Into<Keys>,
+        R: RangeBounds<usize>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.values_from_fn_bounded(keys, range, FromStr::from_str)
+    }
+
+    /// Parses multiple key-value pairs with a limited number of occurrences using a specified function.
+    ///
+    /// The same as [`values_from_fn`], but checks that the number of values
+    /// is within the `range`, like `1..` or `..=2`.
+    ///
+    /// # Errors
+    ///
+    /// - When the number of values is outside the `range`.
+    ///
+    /// [`values_from_fn`]: struct.Arguments.html#method.values_from_fn
+    pub fn values_from_fn_bounded<A, R, T, E, F>(
+        &mut self,
+        keys: A,
+        range: R,
+        f: F,
+    ) -> Result<Vec<T>, Error>
+    where
+        A: Into<Keys>,
+        R: RangeBounds<usize>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        let values = self.values_from_fn(keys, f)?;
+        let (min, max) = count_bounds(&range);
+        check_occurrences(keys, values.len(), min, max)?;
+        Ok(values)
+    }
+
+    /// Parses the last occurrence of a key-value pair using `FromStr` trait.
+    ///
+    /// This is a shorthand for `last_value_from_fn("--key", FromStr::from_str)`
+    pub fn last_value_from_str<A, T>(&mut self, keys: A) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.last_value_from_fn(keys, FromStr::from_str)
+    }
+
+    /// Parses the last occurrence of a key-value pair using a specified function.
+    ///
+    /// Unlike [`value_from_fn`], consumes all occurrences of the option
+    /// and returns the last one. Useful when a shell alias appends
+    /// an overriding option: `--color always --color never` results in `never`.
+    ///
+    /// All occurrences must be valid.
+    ///
+    /// [`value_from_fn`]: struct.Arguments.html#method.value_from_fn
+    pub fn last_value_from_fn<A, T, E, F>(&mut self, keys: A, f: F) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
+        match self.opt_last_value_from_fn(keys, f) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses the last occurrence of an optional key-value pair using `FromStr` trait.
+    ///
+    /// This is a shorthand for `opt_last_value_from_fn("--key", FromStr::from_str)`
+    pub fn opt_last_value_from_str<A, T>(&mut self, keys: A) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.opt_last_value_from_fn(keys, FromStr::from_str)
+    }
+
+    /// Parses the last occurrence of an optional key-value pair using a specified function.
+    ///
+    /// The same as [`last_value_from_fn`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`last_value_from_fn`]: struct.Arguments.html#method.last_value_from_fn
+    pub fn opt_last_value_from_fn<A, T, E, F>(&mut self, keys: A, f: F) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.values_from_fn(keys, f).map(|mut values| values.pop())
+    }
+
+    /// Parses a key followed by multiple values into a tuple using `FromStr` trait.
+    ///
+    /// Consumes exactly as many arguments after the key as there are tuple elements,
+    /// so `--size 10 20` can be parsed as `(u32, u32)`.
+    /// Only space separated values are supported.
+    ///
+    /// Must be used only once for each option.
+    ///
+    /// # Errors
+    ///
+    /// - When option is not present.
+    /// - When there are not enough values.
+    /// - When a value is not a UTF-8 string.
+    /// - When value parsing failed. The error mentions which value it was.
+    pub fn values_tuple_from_str<A: Into<Keys>, T: FromStrTuple>(
+        &mut self,
+        keys: A,
+    ) -> Result<T, Error> {
+        let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
+        match self.opt_values_tuple_from_str(keys) {
+            Ok(Some(v)) => Ok(v),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses an optional key followed by multiple values into a tuple using `FromStr` trait.
+    ///
+    /// The same as [`values_tuple_from_str`], but returns `Ok(None)` when option is not present.
+    ///
+    /// [`values_tuple_from_str`]: struct.Arguments.html#method.values_tuple_from_str
+    pub fn opt_values_tuple_from_str<A: Into<Keys>, T: FromStrTuple>(
+        &mut self,
+        keys: A,
+    ) -> Result<Option<T>, Error> {
+        let keys = keys.into();
+        self.mark_used(keys, UsageKind::Option);
+
+        let (key, idx) = match self.find_values(keys, T::LEN) {
+            Ok(Some(v)) => v,
+            Ok(None) => return Ok(None),
+            Err(e) => return self.fail(e),
+        };
+
+        let result = {
+            let values: Vec<&str> = self.args[idx + 1..idx + 1 + T::LEN].iter()
+                .filter_map(|v| arg_to_str(v))
+                .collect();
+
+            match T::from_str_tuple(&values) {
+                Ok(value) => {
+                    for value in &values {
+                        self.notify(Event::Value { key, value, position: self.positions[idx] });
                     }
+                    Ok(value)
                 }
+                Err((n, cause)) => Err(Error::OptionParsingFailed {
+                    key,
+                    value: values[n].to_string(),
+                    cause: format!("value {} of {}: {}", n + 1, T::LEN, cause),
+                }),
             }
+        };
 
-            // Check length, otherwise String::drain will panic.
-            if value_range.end - value_range.start == 0 {
-                return Err(Error::OptionWithoutAValue(key));
-            }
+        let value = match result {
+            Ok(v) => v,
+            Err(e) => return self.fail(e),
+        };
+        self.consume_key(key);
+        for _ in 0..1 + T::LEN {
+            self.take(idx);
+        }
+        Ok(Some(value))
+    }
 
-            // Extract `value` from `--key="value"`.
-            let value = &value[value_range];
+    // Checks that the key is followed by `count` UTF-8 values.
+    #[inline(never)]
+    fn find_values(
+        &self,
+        keys: Keys,
+        count: usize,
+    ) -> Result<Option<(&'static str, usize)>, Error> {
+        let (idx, key) = match self.index_of(keys) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
 
-            if value.is_empty() {
-                return Err(Error::OptionWithoutAValue(key));
-            }
+        if self.args.len() < idx + 1 + count {
+            return Err(Error::OptionWithoutAValue(key));
+        }
 
-            Ok(Some((value, PairKind::SingleArgument, idx)))
-        } else {
-            Ok(None)
+        for value in &self.args[idx + 1..idx + 1 + count] {
+            self.check_value(key, value)?;
+            os_to_str(value)?;
         }
+
+        Ok(Some((key, idx)))
+    }
+
+    /// Takes all arguments after a key up to a terminator, like `find -exec ... ;`.
+    ///
+    /// The key and the terminator are removed. Arguments in between are returned as is,
+    /// even when they look like flags or options.
+    /// Each call handles the next occurrence of the key.
+    /// Returns `None` when the key is not present.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(
+    ///     vec!["--exec".into(), "rm".into(), "-f".into(), ";".into(), "-v".into()]
+    /// );
+    /// assert_eq!(args.values_until("--exec", ";").unwrap().unwrap(), ["rm", "-f"]);
+    /// assert!(args.contains("-v"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When there is no terminator after the key.
+    pub fn values_until<A: Into<Keys>>(
+        &mut self,
+        keys: A,
+        terminator: &'static str,
+    ) -> Result<Option<Vec<Arg>>, Error> {
+        self.values_until_impl(keys.into(), terminator)
     }
 
-    // The whole logic must be type-independent to prevent monomorphization.
-    #[cfg(not(any(feature = "eq-separator", feature = "short-space-opt")))]
     #[inline(never)]
-    fn find_value(
+    fn values_until_impl(
         &mut self,
         keys: Keys,
-    ) -> Result<Option<(&str, PairKind, usize)>, Error> {
-        if let Some((idx, key)) = self.index_of(keys) {
-            // Parse a `--key value` pair.
+        terminator: &'static str,
+    ) -> Result<Option<Vec<Arg>>, Error> {
+        self.mark_used(keys, UsageKind::Option);
 
-            let value = match self.0.get(idx + 1) {
-                Some(v) => v,
-                None => return Err(Error::OptionWithoutAValue(key)),
-            };
+        let (idx, key) = match self.index_of(keys) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
 
-            let value = os_to_str(value)?;
-            Ok(Some((value, PairKind::TwoArguments, idx)))
-        } else {
-            Ok(None)
+        let count = match self.args[idx + 1..].iter().position(|arg| {
+            arg_to_str(arg) == Some(terminator)
+        }) {
+            Some(count) => count,
+            None => return self.fail(Error::MissingTerminator { key, terminator }),
+        };
+
+        if self.observer.is_some() {
+            let position = self.positions[idx];
+            for value in &self.args[idx + 1..idx + 1 + count] {
+                self.notify(Event::Value { key, value: &arg_to_lossy(value), position });
+            }
         }
+
+        self.consume_key(key);
+        self.take(idx);
+        let values = (0..count).map(|_| self.take(idx)).collect();
+        self.take(idx);
+        Ok(Some(values))
     }
 
-    /// Parses multiple key-value pairs into the `Vec` using `FromStr` trait.
+    /// Parses multiple `NAME=VALUE` definitions.
     ///
-    /// This is a shorthand for `values_from_fn("--key", FromStr::from_str)`
-    pub fn values_from_str<A, T>(&mut self, keys: A) -> Result<Vec<T>, Error>
-        where
-            A: Into<Keys>,
-            T: FromStr,
-            <T as FromStr>::Err: Display,
-    {
-        self.values_from_fn(keys, FromStr::from_str)
+    /// Useful for compiler/make style options like `-D NAME=VALUE` or `--env KEY=VALUE`.
+    ///
+    /// Each value is split on the first `=`, so `-D A=B=C` produces `("A", "B=C")`.
+    /// The order of definitions is preserved.
+    ///
+    /// This method simply executes [`values_from_fn`] with a splitting function.
+    ///
+    /// # Errors
+    ///
+    /// - When a value doesn't contain `=`.
+    ///
+    /// [`values_from_fn`]: struct.Arguments.html#method.values_from_fn
+    pub fn values_from_str_map<A: Into<Keys>>(
+        &mut self,
+        keys: A,
+    ) -> Result<Vec<(String, String)>, Error> {
+        self.values_from_fn(keys, split_definition)
+    }
+
+    /// Extracts all options under a specified namespace into a separate parser.
+    ///
+    /// For example, `take_scope("--ssl-")` moves `--ssl-cert path --ssl-verify`
+    /// into a new parser as `--cert path --verify`, so a module can parse
+    /// its own options without knowing the namespace. The prefix must start with `--`.
+    ///
+    /// Since there is no way to tell flags and options apart, an argument following
+    /// the key is treated as its value, unless it looks like a flag or an option.
+    /// So a scoped flag must not be followed by a free-standing argument.
+    /// `--ssl-key=path` is moved as `--key=path`.
+    ///
+    /// The new parser inherits settings and original positions.
+    pub fn take_scope(&mut self, prefix: &str) -> Arguments {
+        debug_assert!(prefix.starts_with("--"), "a scope prefix should start with '--'");
+
+        let mut scope = self.empty_like();
+        let mut idx = 0;
+        while idx < self.search_end() {
+            let key = match arg_to_str(&self.args[idx]) {
+                Some(s) if s.len() > prefix.len() && s.starts_with(prefix) => {
+                    format!("--{}", &s[prefix.len()..])
+                }
+                _ => {
+                    idx += 1;
+                    continue;
+                }
+            };
+
+            let has_value = !key.contains('=')
+                && self.args.get(idx + 1).map(|v| !is_option_like(v)).unwrap_or(false);
+
+            self.record(Stat::Allocation);
+            scope.args.push(arg_from_string(key));
+            scope.positions.push(self.positions[idx]);
+            self.take(idx);
+            if has_value {
+                scope.positions.push(self.positions[idx]);
+                let value = self.take(idx);
+                scope.args.push(value);
+            }
+        }
+
+        scope
     }
 
-    /// Parses multiple key-value pairs into the `Vec` using a specified function.
-    ///
-    /// This functions can be used to parse arguments like:<br>
-    /// `--file /path1 --file /path2 --file /path3`<br>
-    /// But not `--file /path1 /path2 /path3`.
-    ///
-    /// Arguments can also be separated: `--file /path1 --some-flag --file /path2`
+    /// Consumes all arguments that start with a specified prefix.
     ///
-    /// This method simply executes [`opt_value_from_fn`] multiple times.
+    /// Useful for forwarding opaque options to a subsystem, like `-Xmx2g` or `-Xlog=gc`.
     ///
-    /// An empty `Vec` is not an error.
+    /// The prefix is removed and the rest is split on the first `=`,
+    /// so `-Xlog=gc` results in `("log", Some("gc"))` and `-Xmx2g` in `("mx2g", None)`.
+    /// The order of arguments is preserved.
     ///
-    /// [`opt_value_from_fn`]: struct.Arguments.html#method.opt_value_from_fn
-    pub fn values_from_fn<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<Vec<T>, Error> {
-        let keys = keys.into();
-
+    /// Arguments equal to the prefix and non UTF-8 arguments are ignored.
+    pub fn values_with_prefix(&mut self, prefix: &str) -> Vec<(String, Option<String>)> {
         let mut values = Vec::new();
-        loop {
-            match self.opt_value_from_fn(keys, f) {
-                Ok(Some(v)) => values.push(v),
-                Ok(None) => break,
-                Err(e) => return Err(e),
-            }
+        let mut idx = 0;
+        while idx < self.search_end() {
+            let value = match arg_to_str(&self.args[idx]) {
+                Some(s) if s.len() > prefix.len() && s.starts_with(prefix) => {
+                    let s = &s[prefix.len()..];
+                    match s.find('=') {
+                        Some(i) => (s[..i].to_string(), Some(s[i + 1..].to_string())),
+                        None => (s.to_string(), None),
+                    }
+                }
+                _ => {
+                    idx += 1;
+                    continue;
+                }
+            };
+
+            values.push(value);
+            self.take(idx);
         }
 
-        Ok(values)
+        values
     }
 
     /// Parses a key-value pair using a specified function.
@@ -447,15 +2970,18 @@ impl Arguments {
     ///   Only [`value_from_fn`] supports `=` separator.
     ///
     /// [`value_from_fn`]: struct.Arguments.html#method.value_from_fn
-    pub fn value_from_os_str<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&OsStr) -> Result<T, E>,
-    ) -> Result<T, Error> {
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn value_from_os_str<A, T, E, F>(&mut self, keys: A, f: F) -> Result<T, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&OsStr) -> Result<T, E>,
+    {
         let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Required);
         match self.opt_value_from_os_str(keys, f) {
             Ok(Some(v)) => Ok(v),
-            Ok(None) => Err(Error::MissingOption(keys)),
+            Ok(None) => self.fail(Error::MissingOption(keys)),
             Err(e) => Err(e),
         }
     }
@@ -465,41 +2991,58 @@ impl Arguments {
     /// The same as [`value_from_os_str`], but returns `Ok(None)` when option is not present.
     ///
     /// [`value_from_os_str`]: struct.Arguments.html#method.value_from_os_str
-    pub fn opt_value_from_os_str<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&OsStr) -> Result<T, E>,
-    ) -> Result<Option<T>, Error> {
-        self.opt_value_from_os_str_impl(keys.into(), f)
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn opt_value_from_os_str<A, T, E, F>(&mut self, keys: A, mut f: F) -> Result<Option<T>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&OsStr) -> Result<T, E>,
+    {
+        self.opt_value_from_os_str_impl(keys.into(), &mut f)
     }
 
-    #[inline(never)]
+    #[cfg(not(feature = "utf8-only"))]
+    #[inline]
     fn opt_value_from_os_str_impl<T, E: Display>(
         &mut self,
         keys: Keys,
-        f: fn(&OsStr) -> Result<T, E>,
+        f: &mut dyn FnMut(&OsStr) -> Result<T, E>,
     ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.opt_value_os_str_erased(keys, f))
+    }
+
+    #[cfg(not(feature = "utf8-only"))]
+    #[inline(never)]
+    fn opt_value_os_str_erased(&mut self, keys: Keys, f: ErasedFn<OsStr>) -> Result<(), Error> {
+        self.mark_used(keys, UsageKind::Option);
+
         if let Some((idx, key)) = self.index_of(keys) {
             // Parse a `--key value` pair.
 
-            let value = match self.0.get(idx + 1) {
+            let value = match self.args.get(idx + 1) {
                 Some(v) => v,
-                None => return Err(Error::OptionWithoutAValue(key)),
+                None => return self.fail(Error::OptionWithoutAValue(key)),
             };
 
+            if let Err(e) = self.check_value(key, value) {
+                return self.fail(e);
+            }
+
             match f(value) {
-                Ok(value) => {
+                Ok(()) => {
+                    let position = self.positions[idx];
+                    self.notify(Event::Value { key, value: &value.to_string_lossy(), position });
+
                     // Remove only when all checks are passed.
-                    self.0.remove(idx);
-                    self.0.remove(idx);
-                    Ok(Some(value))
-                }
-                Err(e) => {
-                    Err(Error::ArgumentParsingFailed { cause: error_to_string(e) })
+                    self.consume_key(key);
+                    self.take(idx);
+                    self.take(idx);
+                    Ok(())
                 }
+                Err(cause) => self.fail(Error::ArgumentParsingFailed { cause }),
             }
         } else {
-            Ok(None)
+            Ok(())
         }
     }
 
@@ -513,15 +3056,18 @@ impl Arguments {
     ///
     /// [`opt_value_from_os_str`]: struct.Arguments.html#method.opt_value_from_os_str
     /// [`values_from_fn`]: struct.Arguments.html#method.values_from_fn
-    pub fn values_from_os_str<A: Into<Keys>, T, E: Display>(
-        &mut self,
-        keys: A,
-        f: fn(&OsStr) -> Result<T, E>,
-    ) -> Result<Vec<T>, Error> {
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn values_from_os_str<A, T, E, F>(&mut self, keys: A, mut f: F) -> Result<Vec<T>, Error>
+    where
+        A: Into<Keys>,
+        E: Display,
+        F: FnMut(&OsStr) -> Result<T, E>,
+    {
         let keys = keys.into();
+        self.mark_usage(keys, UsageKind::Values);
         let mut values = Vec::new();
         loop {
-            match self.opt_value_from_os_str(keys, f) {
+            match self.opt_value_from_os_str_impl(keys, &mut f) {
                 Ok(Some(v)) => values.push(v),
                 Ok(None) => break,
                 Err(e) => return Err(e),
@@ -531,14 +3077,26 @@ impl Arguments {
         Ok(values)
     }
 
+    // Returns the end of the arguments range, where flags and options are searched.
+    #[inline(never)]
+    fn search_end(&self) -> usize {
+        if self.trailing_var_arg {
+            self.args.iter().position(|v| !is_option_like(v)).unwrap_or(self.args.len())
+        } else {
+            self.args.len()
+        }
+    }
+
     #[inline(never)]
     fn index_of(&self, keys: Keys) -> Option<(usize, &'static str)> {
         // Do not unroll loop to save space, because it creates a bigger file.
         // Which is strange, since `index_of2` actually benefits from it.
 
+        self.record(Stat::Scan);
+        let args = &self.args[..self.search_end()];
         for key in &keys.0 {
             if !key.is_empty() {
-                if let Some(i) = self.0.iter().position(|v| v == key) {
+                if let Some(i) = args.iter().position(|v| v == key) {
                     return Some((i, key));
                 }
             }
@@ -552,14 +3110,17 @@ impl Arguments {
     fn index_of2(&self, keys: Keys) -> Option<(usize, &'static str)> {
         // Loop unroll to save space.
 
+        self.record(Stat::Scan);
+        let args = &self.args[..self.search_end()];
+
         if !keys.first().is_empty() {
-            if let Some(i) = self.0.iter().position(|v| index_predicate(v, keys.first())) {
+            if let Some(i) = args.iter().position(|v| index_predicate(v, keys.first())) {
                 return Some((i, keys.first()));
             }
         }
 
         if !keys.second().is_empty() {
-            if let Some(i) = self.0.iter().position(|v| index_predicate(v, keys.second())) {
+            if let Some(i) = args.iter().position(|v| index_predicate(v, keys.second())) {
                 return Some((i, keys.second()));
             }
         }
@@ -567,6 +3128,64 @@ impl Arguments {
         None
     }
 
+    /// Checks that the number of free-standing arguments is within the `range`.
+    ///
+    /// All remaining arguments are counted, therefore flags and options
+    /// must be parsed beforehand. Allows reporting a wrong number of arguments
+    /// at once, instead of failing on the first missing one.
+    /// With the `wild` feature, a pattern is counted as a single argument.
+    ///
+    /// Returns the number of arguments.
+    ///
+    /// ```
+    /// let args = pico_args::Arguments::from_vec(vec!["a".into()]);
+    /// assert_eq!(args.expect_free(1..=2).unwrap(), 1);
+    /// assert_eq!(args.expect_free(2..).unwrap_err().to_string(),
+    ///            "expected at least 2 arguments, got 1");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When the number of arguments is outside the `range`.
+    pub fn expect_free<R: RangeBounds<usize>>(&self, range: R) -> Result<usize, Error> {
+        let (min, max) = count_bounds(&range);
+        self.expect_free_impl(min, max, &[])
+    }
+
+    /// Checks that there is a free-standing argument for each name.
+    ///
+    /// The same as [`expect_free`], but the error mentions the names.
+    ///
+    /// ```
+    /// let args = pico_args::Arguments::from_vec(vec!["a".into()]);
+    /// assert_eq!(args.expect_free_named(&["SRC", "DST"]).unwrap_err().to_string(),
+    ///            "expected 2 arguments (SRC, DST), got 1");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When the number of arguments differs from the number of names.
+    ///
+    /// [`expect_free`]: struct.Arguments.html#method.expect_free
+    pub fn expect_free_named(&self, names: &'static [&'static str]) -> Result<(), Error> {
+        self.expect_free_impl(names.len(), Some(names.len()), names).map(|_| ())
+    }
+
+    #[inline(never)]
+    fn expect_free_impl(
+        &self,
+        min: usize,
+        max: Option<usize>,
+        names: &'static [&'static str],
+    ) -> Result<usize, Error> {
+        let count = self.args.len();
+        if count < min || max.map_or(false, |max| count > max) {
+            return self.fail(Error::ArgumentCountOutOfRange { count, min, max, names });
+        }
+
+        Ok(count)
+    }
+
     /// Parses a free-standing argument using `FromStr` trait.
     ///
     /// This is a shorthand for `free_from_fn(FromStr::from_str)`
@@ -597,84 +3216,500 @@ impl Arguments {
     /// - When argument is not present.
     ///
     /// [`free_from_os_str`]: struct.Arguments.html#method.free_from_os_str
+    pub fn free_from_fn<T, E, F>(&mut self, mut f: F) -> Result<T, Error>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.usage.free_required.push("ARG");
+        match self.opt_free_from_fn_impl("", &mut f)? {
+            Some(v) => Ok(v),
+            None => self.fail(Error::MissingArgument),
+        }
+    }
+
+    /// Parses a named free-standing argument using `FromStr` trait.
+    ///
+    /// This is a shorthand for `free_from_fn_named("NAME", FromStr::from_str)`
+    pub fn free_from_str_named<T>(&mut self, name: &'static str) -> Result<T, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.free_from_fn_named(name, FromStr::from_str)
+    }
+
+    /// Parses a named free-standing argument using a specified function.
+    ///
+    /// The same as [`free_from_fn`], but errors and [`usage`] mention the `name`
+    /// placeholder, like `invalid value for <FILE>: ...`.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["x".into()]);
+    /// assert_eq!(args.free_from_str_named::<u32>("COUNT").unwrap_err().to_string(),
+    ///            "invalid value for <COUNT>: invalid digit found in string");
+    /// assert_eq!(args.free_from_str_named::<u32>("COUNT").unwrap_err().to_string(),
+    ///            "the <COUNT> argument is missing");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When argument is not a UTF-8 string.
+    /// - When argument parsing failed.
+    /// - When argument is not present.
+    ///
+    /// [`free_from_fn`]: struct.Arguments.html#method.free_from_fn
+    /// [`usage`]: struct.Arguments.html#method.usage
+    pub fn free_from_fn_named<T, E, F>(&mut self, name: &'static str, mut f: F) -> Result<T, Error>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.usage.free_required.push(name);
+        match self.opt_free_from_fn_impl(name, &mut f)? {
+            Some(v) => Ok(v),
+            None => self.fail(Error::MissingNamedArgument(name)),
+        }
+    }
+
+    /// Parses a free-standing argument using a specified function.
+    ///
+    /// The same as [`free_from_fn`], but parses `&OsStr` instead of `&str`.
+    ///
+    /// [`free_from_fn`]: struct.Arguments.html#method.free_from_fn
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn free_from_os_str<T, E, F>(&mut self, mut f: F) -> Result<T, Error>
+    where
+        E: Display,
+        F: FnMut(&OsStr) -> Result<T, E>,
+    {
+        self.usage.free_required.push("ARG");
+        match self.opt_free_from_os_str_impl(&mut f)? {
+            Some(v) => Ok(v),
+            None => self.fail(Error::MissingArgument),
+        }
+    }
+
+    /// Parses an optional free-standing argument using `FromStr` trait.
+    ///
+    /// The same as [`free_from_str`], but returns `Ok(None)` when argument is not present.
+    ///
+    /// [`free_from_str`]: struct.Arguments.html#method.free_from_str
+    pub fn opt_free_from_str<T>(&mut self) -> Result<Option<T>, Error>
+        where
+            T: FromStr,
+            <T as FromStr>::Err: Display,
+    {
+        self.opt_free_from_fn(FromStr::from_str)
+    }
+
+    /// Parses an optional free-standing argument using a specified function.
+    ///
+    /// The same as [`free_from_fn`], but returns `Ok(None)` when argument is not present.
+    ///
+    /// [`free_from_fn`]: struct.Arguments.html#method.free_from_fn
+    pub fn opt_free_from_fn<T, E, F>(&mut self, mut f: F) -> Result<Option<T>, Error>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.usage.free_optional += 1;
+        self.opt_free_from_fn_impl("", &mut f)
+    }
+
+    // An empty `name` stands for an unnamed argument.
+    #[inline]
+    fn opt_free_from_fn_impl<T, E: Display>(
+        &mut self,
+        name: &'static str,
+        f: &mut dyn FnMut(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.opt_free_erased(name, f))
+    }
+
     #[inline(never)]
-    pub fn free_from_fn<T, E: Display>(
+    fn opt_free_erased(&mut self, name: &'static str, f: ErasedFn<str>) -> Result<(), Error> {
+        if self.args.is_empty() {
+            Ok(())
+        } else {
+            #[cfg(feature = "wild")]
+            self.expand_wildcards(0);
+
+            let position = self.positions[0];
+            let value = self.take(0);
+            let value = match arg_to_str(&value) {
+                Some(v) => v,
+                None => return self.fail(Error::NonUtf8Argument),
+            };
+
+            match f(value) {
+                Ok(()) => {
+                    self.notify(Event::Free { value, position });
+                    Ok(())
+                }
+                Err(cause) if name.is_empty() => self.fail(Error::Utf8ArgumentParsingFailed {
+                    value: value.to_string(),
+                    cause,
+                }),
+                Err(cause) => self.fail(Error::NamedArgumentParsingFailed {
+                    name,
+                    value: value.to_string(),
+                    cause,
+                }),
+            }
+        }
+    }
+
+    /// Parses an optional free-standing argument, skipping flags, using `FromStr` trait.
+    ///
+    /// This is a shorthand for `opt_free_skip_flags_from_fn(FromStr::from_str)`
+    pub fn opt_free_skip_flags_from_str<T>(&mut self) -> Result<Option<T>, Error>
+        where
+            T: FromStr,
+            <T as FromStr>::Err: Display,
+    {
+        self.opt_free_skip_flags_from_fn(FromStr::from_str)
+    }
+
+    /// Parses an optional free-standing argument, skipping flags, using a specified function.
+    ///
+    /// The same as [`opt_free_from_fn`], but parses the first argument that doesn't
+    /// look like a flag or an option. Skipped arguments are left in place,
+    /// so they can be checked via [`contains`] afterwards.
+    ///
+    /// `-` and `--` are not skipped.
+    ///
+    /// Keep in mind that the option values are not flags, so `--width 10 file`
+    /// will return `10`. Options must be parsed beforehand.
+    ///
+    /// [`opt_free_from_fn`]: struct.Arguments.html#method.opt_free_from_fn
+    /// [`contains`]: struct.Arguments.html#method.contains
+    pub fn opt_free_skip_flags_from_fn<T, E, F>(&mut self, mut f: F) -> Result<Option<T>, Error>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.usage.free_optional += 1;
+        self.opt_free_skip_flags_from_fn_impl(&mut f)
+    }
+
+    #[inline]
+    fn opt_free_skip_flags_from_fn_impl<T, E: Display>(
         &mut self,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<T, Error> {
-        self.opt_free_from_fn(f)?.ok_or(Error::MissingArgument)
+        f: &mut dyn FnMut(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.opt_free_skip_flags_erased(f))
+    }
+
+    #[inline(never)]
+    fn opt_free_skip_flags_erased(&mut self, f: ErasedFn<str>) -> Result<(), Error> {
+        self.record(Stat::Scan);
+        let idx = match self.args.iter().position(|v| !is_option_like(v)) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+
+        #[cfg(feature = "wild")]
+        self.expand_wildcards(idx);
+
+        let value = match arg_to_str(&self.args[idx]) {
+            Some(v) => v,
+            None => return self.fail(Error::NonUtf8Argument),
+        };
+
+        match f(value) {
+            Ok(()) => {
+                self.notify(Event::Free { value, position: self.positions[idx] });
+                self.take(idx);
+                Ok(())
+            }
+            Err(cause) => self.fail(Error::Utf8ArgumentParsingFailed {
+                value: value.to_string(),
+                cause,
+            }),
+        }
     }
 
     /// Parses a free-standing argument using a specified function.
     ///
-    /// The same as [`free_from_fn`], but parses `&OsStr` instead of `&str`.
+    /// The same as [`free_from_os_str`], but returns `Ok(None)` when argument is not present.
+    ///
+    /// [`free_from_os_str`]: struct.Arguments.html#method.free_from_os_str
+    #[cfg(not(feature = "utf8-only"))]
+    pub fn opt_free_from_os_str<T, E, F>(&mut self, mut f: F) -> Result<Option<T>, Error>
+    where
+        E: Display,
+        F: FnMut(&OsStr) -> Result<T, E>,
+    {
+        self.usage.free_optional += 1;
+        self.opt_free_from_os_str_impl(&mut f)
+    }
+
+    #[cfg(not(feature = "utf8-only"))]
+    #[inline]
+    fn opt_free_from_os_str_impl<T, E: Display>(
+        &mut self,
+        f: &mut dyn FnMut(&OsStr) -> Result<T, E>,
+    ) -> Result<Option<T>, Error> {
+        parse_erased(f, |f| self.opt_free_os_str_erased(f))
+    }
+
+    #[cfg(not(feature = "utf8-only"))]
+    #[inline(never)]
+    fn opt_free_os_str_erased(&mut self, f: ErasedFn<OsStr>) -> Result<(), Error> {
+        if self.args.is_empty() {
+            Ok(())
+        } else {
+            #[cfg(feature = "wild")]
+            self.expand_wildcards(0);
+
+            let position = self.positions[0];
+            let value = self.take(0);
+            match f(value.as_os_str()) {
+                Ok(()) => {
+                    self.notify(Event::Free { value: &value.to_string_lossy(), position });
+                    Ok(())
+                }
+                Err(cause) => self.fail(Error::ArgumentParsingFailed { cause }),
+            }
+        }
+    }
+
+    // Replaces a free-standing argument with matching paths on Windows,
+    // where the shell doesn't expand wildcards. All paths share the original position.
+    #[cfg(feature = "wild")]
+    fn expand_wildcards(&mut self, idx: usize) {
+        if !cfg!(windows) {
+            return;
+        }
+
+        let paths = match arg_to_str(&self.args[idx]).and_then(wild::expand) {
+            Some(paths) => paths,
+            None => return,
+        };
+
+        let position = self.positions[idx];
+        let count = paths.len();
+        self.args.splice(idx..=idx, paths.into_iter().map(arg_from_string));
+        self.positions.splice(idx..=idx, std::iter::repeat(position).take(count));
+    }
+
+    // Expands all remaining arguments that don't look like flags or options.
+    #[cfg(feature = "wild")]
+    fn expand_all_wildcards(&mut self) {
+        // Go backwards, so expanded paths do not shift the unprocessed arguments.
+        for idx in (0..self.args.len()).rev() {
+            if !is_option_like(&self.args[idx]) {
+                self.expand_wildcards(idx);
+            }
+        }
+    }
+
+    /// Returns the original positions of the consumed arguments, in the consumption order.
+    ///
+    /// Positions are zero-based and do not include the executable path.
+    /// Both keys and values are recorded, so `-w 10` adds two positions.
+    ///
+    /// Useful for diagnostics like "argument 3: invalid value".
+    pub fn consumed(&self) -> &[usize] {
+        &self.consumed
+    }
+
+    /// Returns the original positions of the remaining arguments.
+    ///
+    /// Corresponds to the list returned by [`finish`].
+    ///
+    /// [`finish`]: struct.Arguments.html#method.finish
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// Splits arguments at the first `--` into two parsers.
+    ///
+    /// The `--` itself is removed. The second parser is empty when there is no `--`.
+    /// Both parsers inherit settings and original positions.
+    ///
+    /// ```
+    /// let args = pico_args::Arguments::from_vec(vec!["-v".into(), "--".into(), "-v".into()]);
+    /// let (mut args, mut forwarded) = args.split_at_dash_dash();
+    /// assert!(args.contains("-v"));
+    /// assert!(forwarded.contains("-v"));
+    /// ```
+    pub fn split_at_dash_dash(mut self) -> (Arguments, Arguments) {
+        let mut rest = self.empty_like();
+        if let Some(idx) = self.args.iter().position(|arg| arg_to_str(arg) == Some("--")) {
+            rest.args = self.args.split_off(idx + 1);
+            rest.positions = self.positions.split_off(idx + 1);
+            self.args.pop();
+            self.positions.pop();
+        }
+
+        (self, rest)
+    }
+
+    /// Takes the subcommand and all arguments after it into a separate parser.
+    ///
+    /// The subcommand is the first argument that doesn't look like a flag or an option.
+    /// Arguments before it are left in this parser, so global options and subcommand
+    /// options cannot interfere. Since there is no way to tell flags and options apart,
+    /// global options with separate values, like `--config path`, must be parsed first.
+    ///
+    /// Returns `None` when there is no subcommand before `--`.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(
+    ///     vec!["-v".into(), "build".into(), "-v".into(), "--release".into()]
+    /// );
+    /// let (name, mut sub_args) = args.take_subcommand().unwrap().unwrap();
+    /// assert_eq!(name, "build");
+    /// assert!(sub_args.contains("--release"));
+    /// assert!(args.contains("-v"));
+    /// assert!(!args.contains("-v"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - When the subcommand is not a UTF-8 string.
+    pub fn take_subcommand(&mut self) -> Result<Option<(String, Arguments)>, Error> {
+        // `--` is not option-like as well.
+        let idx = match self.args.iter().position(|arg| !is_option_like(arg)) {
+            Some(idx) if arg_to_str(&self.args[idx]) != Some("--") => idx,
+            _ => return Ok(None),
+        };
+
+        let name = match arg_to_str(&self.args[idx]) {
+            Some(name) => name.to_string(),
+            None => return self.fail(Error::NonUtf8Argument),
+        };
+        self.notify(Event::Free { value: &name, position: self.positions[idx] });
+
+        let mut rest = self.empty_like();
+        rest.args = self.args.split_off(idx + 1);
+        rest.positions = self.positions.split_off(idx + 1);
+        self.take(idx);
+
+        Ok(Some((name, rest)))
+    }
+
+    // Creates an empty parser with the same settings.
+    fn empty_like(&self) -> Arguments {
+        let mut args = Arguments::from_vec(Vec::new());
+        args.trailing_var_arg = self.trailing_var_arg;
+        args.next_position = self.next_position;
+        args.observer = self.observer;
+        args.strict_values = self.strict_values;
+        args.quote_mode = self.quote_mode;
+        args.interpolate_env = self.interpolate_env;
+        #[cfg(feature = "eq-separator")]
+        {
+            args.separator_policy = self.separator_policy;
+        }
+        args
+    }
+
+    /// Returns the number of remaining arguments.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Checks that there are no remaining arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Returns the remaining arguments.
+    ///
+    /// Returns `&[String]` when the `utf8-only` feature is enabled.
+    pub fn remaining_os(&self) -> &[Arg] {
+        &self.args
+    }
+
+    /// Returns an iterator over the remaining arguments.
+    ///
+    /// Doesn't consume anything, so it can be used for custom lookups.
     ///
-    /// [`free_from_fn`]: struct.Arguments.html#method.free_from_fn
+    /// Yields `&str` when the `utf8-only` feature is enabled.
+    pub fn iter(&self) -> impl Iterator<Item = &ArgStr> {
+        self.args.iter().map(|arg| arg.as_ref())
+    }
+
+    fn consume_key(&mut self, key: &'static str) {
+        if !self.consumed_keys.contains(&key) {
+            self.consumed_keys.push(key);
+        }
+    }
+
+    // Removes an argument and records its original position.
     #[inline(never)]
-    pub fn free_from_os_str<T, E: Display>(
-        &mut self,
-        f: fn(&OsStr) -> Result<T, E>,
-    ) -> Result<T, Error> {
-        self.opt_free_from_os_str(f)?.ok_or(Error::MissingArgument)
+    fn take(&mut self, idx: usize) -> Arg {
+        self.record(Stat::Removal);
+        self.consumed.push(self.positions.remove(idx));
+        self.args.remove(idx)
     }
 
-    /// Parses an optional free-standing argument using `FromStr` trait.
+    /// Checks that two options are not used together.
     ///
-    /// The same as [`free_from_str`], but returns `Ok(None)` when argument is not present.
+    /// Both consumed and remaining arguments are checked,
+    /// so can be called before or after parsing.
     ///
-    /// [`free_from_str`]: struct.Arguments.html#method.free_from_str
-    pub fn opt_free_from_str<T>(&mut self) -> Result<Option<T>, Error>
-        where
-            T: FromStr,
-            <T as FromStr>::Err: Display,
-    {
-        self.opt_free_from_fn(FromStr::from_str)
+    /// # Errors
+    ///
+    /// - When both options are present.
+    pub fn check_conflict<A: Into<Keys>, B: Into<Keys>>(&self, a: A, b: B) -> Result<(), Error> {
+        match (self.find_key(a.into()), self.find_key(b.into())) {
+            (Some(a), Some(b)) => self.fail(Error::ConflictingOptions(a, b)),
+            _ => Ok(()),
+        }
     }
 
-    /// Parses an optional free-standing argument using a specified function.
+    /// Checks that an option is used only along with another option.
     ///
-    /// The same as [`free_from_fn`], but returns `Ok(None)` when argument is not present.
+    /// Both consumed and remaining arguments are checked,
+    /// so can be called before or after parsing.
     ///
-    /// [`free_from_fn`]: struct.Arguments.html#method.free_from_fn
-    #[inline(never)]
-    pub fn opt_free_from_fn<T, E: Display>(
-        &mut self,
-        f: fn(&str) -> Result<T, E>,
-    ) -> Result<Option<T>, Error> {
-        if self.0.is_empty() {
-            Ok(None)
-        } else {
-            let value = self.0.remove(0);
-            let value = os_to_str(value.as_os_str())?;
-            match f(&value) {
-                Ok(value) => Ok(Some(value)),
-                Err(e) => Err(Error::Utf8ArgumentParsingFailed {
-                    value: value.to_string(),
-                    cause: error_to_string(e),
-                }),
+    /// # Errors
+    ///
+    /// - When `key` is present, but `required` is not.
+    pub fn check_requirement<A: Into<Keys>, B: Into<Keys>>(
+        &self,
+        key: A,
+        required: B,
+    ) -> Result<(), Error> {
+        let required = required.into();
+        match self.find_key(key.into()) {
+            Some(key) if self.find_key(required).is_none() => {
+                self.fail(Error::MissingRequiredOption { key, required })
             }
+            _ => Ok(()),
         }
     }
 
-    /// Parses a free-standing argument using a specified function.
-    ///
-    /// The same as [`free_from_os_str`], but returns `Ok(None)` when argument is not present.
-    ///
-    /// [`free_from_os_str`]: struct.Arguments.html#method.free_from_os_str
+    // Returns a key that was consumed or is present in the arguments, in any form.
     #[inline(never)]
-    pub fn opt_free_from_os_str<T, E: Display>(
-        &mut self,
-        f: fn(&OsStr) -> Result<T, E>,
-    ) -> Result<Option<T>, Error> {
-        if self.0.is_empty() {
-            Ok(None)
-        } else {
-            let value = self.0.remove(0);
-            match f(value.as_os_str()) {
-                Ok(value) => Ok(Some(value)),
-                Err(e) => Err(Error::ArgumentParsingFailed { cause: error_to_string(e) }),
+    fn find_key(&self, keys: Keys) -> Option<&'static str> {
+        if let Some(key) = keys.iter().find(|key| self.consumed_keys.contains(key)) {
+            return Some(key);
+        }
+
+        if let Some((_, key)) = self.index_of(keys) {
+            return Some(key);
+        }
+
+        #[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
+        {
+            if let Some((_, key)) = self.index_of2(keys) {
+                return Some(key);
             }
         }
+
+        None
+    }
+
+    /// Parses a type that implements [`FromArguments`].
+    ///
+    /// [`FromArguments`]: trait.FromArguments.html
+    pub fn parse<T: FromArguments>(&mut self) -> Result<T, Error> {
+        T::from_args(self)
     }
 
     /// Returns a list of remaining arguments.
@@ -682,8 +3717,71 @@ impl Arguments {
     /// It's up to the caller what to do with them.
     /// One can report an error about unused arguments,
     /// other can use them for further processing.
-    pub fn finish(self) -> Vec<OsString> {
-        self.0
+    ///
+    /// Returns `Vec<String>` when the `utf8-only` feature is enabled.
+    ///
+    /// With the `wild` feature, free-standing arguments are expanded on Windows.
+    #[cfg_attr(not(feature = "wild"), allow(unused_mut))]
+    pub fn finish(mut self) -> Vec<Arg> {
+        #[cfg(feature = "wild")]
+        self.expand_all_wildcards();
+
+        self.args
+    }
+
+    /// Checks that all arguments were consumed.
+    ///
+    /// The same as [`finish`], but returns remaining arguments as an error,
+    /// so they can be reported, ignored or forwarded as is.
+    ///
+    /// ```
+    /// let mut args = pico_args::Arguments::from_vec(vec!["-v".into(), "extra".into()]);
+    /// assert!(args.contains("-v"));
+    /// if let Err(remaining) = args.try_finish() {
+    ///     eprintln!("Warning: unused arguments left: {:?}.", remaining);
+    /// }
+    /// ```
+    ///
+    /// [`finish`]: struct.Arguments.html#method.finish
+    #[cfg_attr(not(feature = "wild"), allow(unused_mut))]
+    pub fn try_finish(mut self) -> Result<(), Vec<Arg>> {
+        #[cfg(feature = "wild")]
+        self.expand_all_wildcards();
+
+        if self.args.is_empty() {
+            Ok(())
+        } else {
+            Err(self.args)
+        }
+    }
+}
+
+impl<S: Into<Arg>> Extend<S> for Arguments {
+    /// Appends arguments.
+    ///
+    /// See [`push`](struct.Arguments.html#method.push).
+    fn extend<I: IntoIterator<Item = S>>(&mut self, args: I) {
+        for arg in args {
+            self.push_arg(arg.into());
+        }
+    }
+}
+
+impl IntoIterator for Arguments {
+    type Item = Arg;
+    type IntoIter = std::vec::IntoIter<Arg>;
+
+    /// Returns an iterator over the remaining arguments.
+    ///
+    /// The same as [`finish`](struct.Arguments.html#method.finish).
+    fn into_iter(self) -> Self::IntoIter {
+        self.args.into_iter()
+    }
+}
+
+impl<S: Into<Arg>> std::iter::FromIterator<S> for Arguments {
+    fn from_iter<I: IntoIterator<Item = S>>(args: I) -> Self {
+        Arguments::from_iter(args)
     }
 }
 
@@ -694,14 +3792,34 @@ fn error_to_string<E: Display>(e: E) -> String {
     e.to_string()
 }
 
+// Checks that an argument looks like a flag or an option.
+// `-` and `--` are not, since they are commonly used as free-standing arguments.
+#[inline(never)]
+fn is_option_like(text: &ArgStr) -> bool {
+    starts_with_dash(text) && arg_to_str(text) != Some("-") && arg_to_str(text) != Some("--")
+}
+
+// Checks for a leading `-`, even in a non UTF-8 argument, like `--bad\xFF`.
+#[cfg(not(feature = "utf8-only"))]
+fn starts_with_dash(text: &OsStr) -> bool {
+    match text.to_str() {
+        Some(s) => s.starts_with('-'),
+        // Only invalid sequences are replaced, so an ASCII prefix is preserved.
+        None => text.to_string_lossy().starts_with('-'),
+    }
+}
+
+#[cfg(feature = "utf8-only")]
+fn starts_with_dash(text: &str) -> bool {
+    text.starts_with('-')
+}
+
 #[cfg(feature = "eq-separator")]
 #[inline(never)]
-fn starts_with_plus_eq(text: &OsStr, prefix: &str) -> bool {
-    if let Some(s) = text.to_str() {
-        if s.get(0..prefix.len()) == Some(prefix) {
-            if s.as_bytes().get(prefix.len()) == Some(&b'=') {
-                return true;
-            }
+fn starts_with_plus_eq(text: &ArgStr, prefix: &str) -> bool {
+    if let Some(s) = arg_to_str(text) {
+        if s.get(0..prefix.len()) == Some(prefix) && s.as_bytes().get(prefix.len()) == Some(&b'=') {
+            return true;
         }
     }
 
@@ -710,11 +3828,11 @@ fn starts_with_plus_eq(text: &OsStr, prefix: &str) -> bool {
 
 #[cfg(feature = "short-space-opt")]
 #[inline(never)]
-fn starts_with_short_prefix(text: &OsStr, prefix: &str) -> bool {
+fn starts_with_short_prefix(text: &ArgStr, prefix: &str) -> bool {
     if prefix.starts_with("--") {
         return false; // Only works for short keys
     }
-    if let Some(s) = text.to_str() {
+    if let Some(s) = arg_to_str(text) {
         if s.get(0..prefix.len()) == Some(prefix) {
             return true;
         }
@@ -725,53 +3843,522 @@ fn starts_with_short_prefix(text: &OsStr, prefix: &str) -> bool {
 
 #[cfg(all(feature = "eq-separator", feature = "short-space-opt"))]
 #[inline]
-fn index_predicate(text: &OsStr, prefix: &str) -> bool {
+fn index_predicate(text: &ArgStr, prefix: &str) -> bool {
     starts_with_plus_eq(text, prefix) || starts_with_short_prefix(text, prefix)
 }
 #[cfg(all(feature = "eq-separator", not(feature = "short-space-opt")))]
 #[inline]
-fn index_predicate(text: &OsStr, prefix: &str) -> bool {
+fn index_predicate(text: &ArgStr, prefix: &str) -> bool {
     starts_with_plus_eq(text, prefix)
 }
 #[cfg(all(feature = "short-space-opt", not(feature = "eq-separator")))]
 #[inline]
-fn index_predicate(text: &OsStr, prefix: &str) -> bool {
+fn index_predicate(text: &ArgStr, prefix: &str) -> bool {
     starts_with_short_prefix(text, prefix)
 }
 
-#[cfg(any(feature = "eq-separator", feature = "short-space-opt"))]
-#[inline]
-fn ends_with(text: &str, c: u8) -> bool {
-    if text.is_empty() {
-        false
-    } else {
-        text.as_bytes()[text.len() - 1] == c
+#[inline(never)]
+fn check_occurrences(keys: Keys, count: usize, min: usize, max: Option<usize>) -> Result<(), Error> {
+    let too_many = match max {
+        Some(max) => count > max,
+        None => false,
+    };
+
+    if count < min || too_many {
+        let key = if keys.second().is_empty() { keys.first() } else { keys.second() };
+        return Err(Error::OccurrencesOutOfRange { key, count, min, max });
+    }
+
+    Ok(())
+}
+
+// Checks for `-5`, `-1.5` or `-.5`.
+fn is_negative_number(s: &str) -> bool {
+    match s.strip_prefix('-') {
+        Some(rest) => {
+            let rest = rest.strip_prefix('.').unwrap_or(rest);
+            rest.starts_with(|c: char| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+fn count_bounds<R: RangeBounds<usize>>(range: &R) -> (usize, Option<usize>) {
+    let min = match range.start_bound() {
+        Bound::Included(n) => *n,
+        Bound::Excluded(n) => *n + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let max = match range.end_bound() {
+        Bound::Included(n) => Some(*n),
+        Bound::Excluded(n) => Some(n.saturating_sub(1)),
+        Bound::Unbounded => None,
+    };
+
+    (min, max)
+}
+
+fn range_to_string<T: Display, R: RangeBounds<T>>(range: &R) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(v) => v.to_string(),
+        Bound::Excluded(v) => format!("{}<", v),
+        Bound::Unbounded => String::new(),
+    };
+
+    match range.end_bound() {
+        Bound::Included(v) => format!("{}..={}", start, v),
+        Bound::Excluded(v) => format!("{}..{}", start, v),
+        Bound::Unbounded => format!("{}..", start),
+    }
+}
+
+fn split_definition(s: &str) -> Result<(String, String), &'static str> {
+    match s.find('=') {
+        Some(idx) => Ok((s[..idx].to_string(), s[idx + 1..].to_string())),
+        None => Err("expected a NAME=VALUE pair"),
+    }
+}
+
+#[inline(never)]
+fn closest_match(name: &str, candidates: &[&'static str]) -> Option<&'static str> {
+    // Allow about one typo per three characters.
+    let max_distance = std::cmp::max(2, name.chars().count() / 3);
+
+    let mut best = None;
+    let mut best_distance = max_distance + 1;
+    for candidate in candidates {
+        let distance = edit_distance(name, candidate);
+        if distance < best_distance {
+            best = Some(*candidate);
+            best_distance = distance;
+        }
+    }
+
+    best
+}
+
+// The Levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let mut row: Vec<usize> = (0..=b.chars().count()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.chars().enumerate() {
+            let substitution = prev + if ca == cb { 0 } else { 1 };
+            prev = row[j + 1];
+            row[j + 1] = std::cmp::min(substitution, std::cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+
+    row[row.len() - 1]
+}
+
+/// Parses a value that follows `=` in `--key=value` (or the key in `-Kvalue`).
+///
+/// The grammar is:
+///
+/// - A value that doesn't start with `"` or `'` is taken verbatim.
+/// - Otherwise, the value must end with the same quote and the quotes are removed.
+/// - Inside double quotes, `\"` and `\\` are unescaped to `"` and `\`.
+///   Any other backslash is preserved, so `"C:\dir"` stays `C:\dir`
+///   and `"C:\temp\"` results in `C:\temp\`.
+///   An unescaped quote inside is preserved as well, so `"a"b"` results in `a"b`.
+/// - Inside single quotes, the content is taken verbatim.
+///
+/// An empty quoted value, like `""`, is allowed.
+///
+/// Returns `None` for an empty value or a quoted one without a matching closing quote,
+/// like `'value"`.
+///
+/// Borrows the input unless an escape sequence had to be processed.
+pub fn parse_eq_value(value: &str) -> Option<Cow<'_, str>> {
+    let quote = match value.as_bytes().first() {
+        Some(&c) if c == b'"' || c == b'\'' => c,
+        Some(_) => return Some(Cow::Borrowed(value)),
+        None => return None,
+    };
+
+    // A closing quote must be the same as an opening one.
+    if value.len() < 2 || value.as_bytes().last() != Some(&quote) {
+        return None;
+    }
+
+    let inner = &value[1..value.len() - 1];
+    if quote == b'\'' || !inner.contains('\\') {
+        return Some(Cow::Borrowed(inner));
+    }
+
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if next == '"' || next == '\\' {
+                    chars.next();
+                    unescaped.push(next);
+                    continue;
+                }
+            }
+        }
+
+        unescaped.push(c);
+    }
+
+    Some(Cow::Owned(unescaped))
+}
+
+// Expands `${NAME}` and, when `percent` is set, `%NAME%` references.
+//
+// Returns `None` when there is nothing to expand and the name of the first
+// undefined variable on error. Unterminated references are kept as is.
+fn expand_vars(
+    text: &str,
+    percent: bool,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<Option<String>, String> {
+    let is_marker = |c: char| c == '$' || (percent && c == '%');
+    if !text.contains(is_marker) {
+        return Ok(None);
+    }
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+    while let Some(i) = rest.find(is_marker) {
+        expanded.push_str(&rest[..i]);
+        let marker = &rest[i..i + 1];
+        let tail = &rest[i + 1..];
+
+        let (name, after) = if marker == "$" {
+            if let Some(tail) = tail.strip_prefix('$') {
+                expanded.push('$');
+                rest = tail;
+                changed = true;
+                continue;
+            }
+
+            match tail.strip_prefix('{').and_then(|t| t.find('}').map(|end| (t, end))) {
+                Some((t, end)) if end != 0 => (&t[..end], &t[end + 1..]),
+                _ => {
+                    expanded.push('$');
+                    rest = tail;
+                    continue;
+                }
+            }
+        } else {
+            match tail.find('%') {
+                // `%%` is an escaped percent sign.
+                Some(0) => {
+                    expanded.push('%');
+                    rest = &tail[1..];
+                    changed = true;
+                    continue;
+                }
+                Some(end) => (&tail[..end], &tail[end + 1..]),
+                None => {
+                    expanded.push('%');
+                    rest = tail;
+                    continue;
+                }
+            }
+        };
+
+        match lookup(name) {
+            Some(value) => expanded.push_str(&value),
+            None => return Err(name.to_string()),
+        }
+        rest = after;
+        changed = true;
     }
+
+    if !changed {
+        return Ok(None);
+    }
+
+    expanded.push_str(rest);
+    Ok(Some(expanded))
+}
+
+#[inline]
+fn os_to_str(text: &ArgStr) -> Result<&str, Error> {
+    arg_to_str(text).ok_or(Error::NonUtf8Argument)
+}
+
+#[cfg(not(feature = "utf8-only"))]
+#[inline]
+fn arg_to_str(text: &OsStr) -> Option<&str> {
+    text.to_str()
+}
+
+#[cfg(feature = "utf8-only")]
+#[inline]
+fn arg_to_str(text: &str) -> Option<&str> {
+    Some(text)
+}
+
+#[cfg(not(feature = "utf8-only"))]
+#[inline]
+fn arg_to_lossy(text: &OsStr) -> Cow<'_, str> {
+    text.to_string_lossy()
+}
+
+#[cfg(feature = "utf8-only")]
+#[inline]
+fn arg_to_lossy(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+#[cfg(not(feature = "utf8-only"))]
+#[inline]
+fn arg_into_string(arg: OsString) -> Result<String, Error> {
+    arg.into_string().map_err(|_| Error::NonUtf8Argument)
+}
+
+#[cfg(feature = "utf8-only")]
+#[inline]
+fn arg_into_string(arg: String) -> Result<String, Error> {
+    Ok(arg)
+}
+
+#[cfg(all(windows, not(feature = "utf8-only")))]
+fn arg_to_utf16(text: &OsStr) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    text.encode_wide().collect()
+}
+
+#[cfg(all(not(windows), not(feature = "utf8-only")))]
+fn arg_to_utf16(text: &OsStr) -> Vec<u16> {
+    text.to_string_lossy().encode_utf16().collect()
+}
+
+#[cfg(feature = "utf8-only")]
+fn arg_to_utf16(text: &str) -> Vec<u16> {
+    text.encode_utf16().collect()
+}
+
+#[cfg(all(windows, not(feature = "utf8-only")))]
+fn arg_from_utf16(text: Vec<u16>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    OsString::from_wide(&text)
+}
+
+#[cfg(all(not(windows), not(feature = "utf8-only")))]
+fn arg_from_utf16(text: Vec<u16>) -> OsString {
+    String::from_utf16_lossy(&text).into()
+}
+
+#[cfg(feature = "utf8-only")]
+fn arg_from_utf16(text: Vec<u16>) -> String {
+    String::from_utf16_lossy(&text)
+}
+
+#[cfg(all(unix, not(feature = "utf8-only")))]
+fn arg_from_bytes(bytes: &[u8]) -> OsString {
+    use std::os::unix::ffi::OsStrExt;
+    OsStr::from_bytes(bytes).to_os_string()
+}
+
+#[cfg(all(not(unix), not(feature = "utf8-only")))]
+fn arg_from_bytes(bytes: &[u8]) -> OsString {
+    String::from_utf8_lossy(bytes).into_owned().into()
 }
 
+#[cfg(feature = "utf8-only")]
+fn arg_from_bytes(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(not(feature = "utf8-only"))]
+#[inline]
+fn arg_from_string(s: String) -> OsString {
+    s.into()
+}
+
+#[cfg(feature = "utf8-only")]
 #[inline]
-fn os_to_str(text: &OsStr) -> Result<&str, Error> {
-    text.to_str().ok_or_else(|| Error::NonUtf8Argument)
+fn arg_from_string(s: String) -> String {
+    s
+}
+
+
+/// A tuple that can be parsed from multiple option values.
+///
+/// Used by [`Arguments::values_tuple_from_str`].
+/// Implemented for tuples of up to 4 `FromStr` elements.
+///
+/// [`Arguments::values_tuple_from_str`]: struct.Arguments.html#method.values_tuple_from_str
+pub trait FromStrTuple: Sized {
+    /// The number of values.
+    const LEN: usize;
+
+    /// Parses exactly `LEN` values.
+    ///
+    /// On error, returns the index of the failed value and the error message.
+    fn from_str_tuple(values: &[&str]) -> Result<Self, (usize, String)>;
+}
+
+macro_rules! impl_from_str_tuple {
+    ($len:expr; $($name:ident $idx:tt),+) => {
+        impl<$($name),+> FromStrTuple for ($($name,)+)
+        where
+            $($name: FromStr, <$name as FromStr>::Err: Display),+
+        {
+            const LEN: usize = $len;
+
+            fn from_str_tuple(values: &[&str]) -> Result<Self, (usize, String)> {
+                Ok(($(
+                    $name::from_str(values[$idx]).map_err(|e| ($idx, error_to_string(e)))?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_str_tuple!(2; A 0, B 1);
+impl_from_str_tuple!(3; A 0, B 1, C 2);
+impl_from_str_tuple!(4; A 0, B 1, C 2, D 3);
+
+
+/// A type that can be parsed from arguments.
+///
+/// Allows splitting common option groups, like logging or TLS options,
+/// into their own structs, which can be reused across applications.
+///
+/// Implemented for tuples of up to 4 elements, which are parsed in order.
+/// Structs declared via [`parse_args!`] implement it as well.
+///
+/// # Example
+///
+/// ```
+/// use pico_args::{Arguments, Error, FromArguments};
+///
+/// struct LoggingOpts {
+///     verbose: bool,
+/// }
+///
+/// impl FromArguments for LoggingOpts {
+///     fn from_args(args: &mut Arguments) -> Result<Self, Error> {
+///         Ok(LoggingOpts { verbose: args.contains("-v") })
+///     }
+/// }
+///
+/// let mut args = Arguments::from_iter(["-v"].iter().cloned());
+/// let logging: LoggingOpts = args.parse().unwrap();
+/// assert!(logging.verbose);
+/// ```
+///
+/// [`parse_args!`]: macro.parse_args.html
+pub trait FromArguments: Sized {
+    /// Parses arguments.
+    fn from_args(args: &mut Arguments) -> Result<Self, Error>;
+}
+
+macro_rules! impl_from_arguments_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: FromArguments),+> FromArguments for ($($name,)+) {
+            fn from_args(args: &mut Arguments) -> Result<Self, Error> {
+                Ok(($($name::from_args(args)?,)+))
+            }
+        }
+    };
 }
 
+impl_from_arguments_tuple!(A, B);
+impl_from_arguments_tuple!(A, B, C);
+impl_from_arguments_tuple!(A, B, C, D);
+
 
 /// A keys container.
 ///
-/// Should not be used directly.
-#[doc(hidden)]
-#[derive(Clone, Copy, Debug)]
+/// Holds a short and/or a long key of a flag or an option, like `-w` and `--width`.
+///
+/// Usually created implicitly from `"--width"` or `["-w", "--width"]`.
+/// Can be found in [`Error::MissingOption`].
+///
+/// [`Error::MissingOption`]: enum.Error.html#variant.MissingOption
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Keys([&'static str; 2]);
 
 impl Keys {
+    /// Creates keys from a short and a long key.
+    ///
+    /// Unlike `From<[&str; 2]>`, which checks keys only in debug builds,
+    /// can be used in a `const` context, where invalid keys fail the build:
+    ///
+    /// ```compile_fail
+    /// const WIDTH: pico_args::Keys = pico_args::Keys::short_long("w", "--width");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// const WIDTH: pico_args::Keys = pico_args::Keys::short_long("-wx", "--width");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - When the short key doesn't start with `-` or starts with `--`.
+    /// - When the short key is not a single character or a repeated character, like `-vv`.
+    /// - When the long key doesn't start with `--`.
+    pub const fn short_long(short: &'static str, long: &'static str) -> Self {
+        let s = short.as_bytes();
+        if s.len() < 2 || s[0] != b'-' || s[1] == b'-' {
+            panic!("the first key should be short, like '-w'");
+        }
+
+        // The same rule as in `validate_shortflag`, but on UTF-8 bytes.
+        let char_len = if s[1] < 0x80 { 1 } else if s[1] < 0xE0 { 2 } else if s[1] < 0xF0 { 3 } else { 4 };
+        if (s.len() - 1) % char_len != 0 {
+            panic!("short keys should be a single character or a repeated character");
+        }
+
+        let mut i = 1 + char_len;
+        while i < s.len() {
+            if s[i] != s[1 + (i - 1) % char_len] {
+                panic!("short keys should be a single character or a repeated character");
+            }
+            i += 1;
+        }
+
+        let l = long.as_bytes();
+        if l.len() < 3 || l[0] != b'-' || l[1] != b'-' {
+            panic!("the second key should be long, like '--width'");
+        }
+
+        Keys([short, long])
+    }
+
+    /// Returns the first key.
+    ///
+    /// This is the short key when both are set.
     #[inline]
-    fn first(&self) -> &'static str {
+    pub fn first(&self) -> &'static str {
         self.0[0]
     }
 
+    /// Returns the second key.
+    ///
+    /// This is the long key when both are set, or an empty string otherwise.
     #[inline]
-    fn second(&self) -> &'static str {
+    pub fn second(&self) -> &'static str {
         self.0[1]
     }
+
+    /// Returns an iterator over the non-empty keys.
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> {
+        let keys = self.0;
+        (0..2).map(move |i| keys[i]).filter(|k| !k.is_empty())
+    }
+}
+
+impl Display for Keys {
+    /// Formats keys as `-w/--width`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.second().is_empty() {
+            f.write_str(self.first())
+        } else {
+            write!(f, "{}/{}", self.first(), self.second())
+        }
+    }
 }
 
 impl From<[&'static str; 2]> for Keys {
@@ -0,0 +1,231 @@
+/// Declares an arguments struct along with its parsing function and help.
+///
+/// Each field has a kind, keys, an optional default value and a doc comment:
+///
+/// - `flag(KEYS)` - a `bool` flag, via [`Arguments::contains`].
+/// - `value(KEYS)` - a required option, via [`Arguments::value_from_str`].
+/// - `value(KEYS) default EXPR` - an option with a default value.
+/// - `optional(KEYS)` - an `Option<T>` option, via [`Arguments::opt_value_from_str`].
+/// - `values(KEYS)` - a `Vec<T>` option, via [`Arguments::values_from_str`].
+/// - `free` - a required free-standing argument, via [`Arguments::free_from_str`].
+/// - `free default EXPR` - a free-standing argument with a default value.
+/// - `optional_free` - an `Option<T>` free-standing argument.
+/// - `flatten` - a nested struct, via [`FromArguments`]. Not listed in `HELP`.
+///
+/// `KEYS` are one or two string literals, like `"--width"` or `"-w", "--width"`.
+///
+/// Expands to the struct itself, a `from_args` function, that parses fields
+/// in the declaration order, a [`FromArguments`] implementation and a `HELP` constant,
+/// which lists the fields with their documentation. Free-standing arguments should be declared last,
+/// since options must be parsed first.
+///
+/// All fields are public. Remaining arguments are left as is,
+/// so `Arguments::finish` can be used afterwards.
+///
+/// # Example
+///
+/// ```
+/// pico_args::parse_args! {
+///     #[derive(Debug)]
+///     struct AppArgs {
+///         /// Prints help information
+///         help: bool = flag("-h", "--help"),
+///         /// Sets width
+///         width: u32 = value("-w", "--width") default 10,
+///         /// Sets an output path
+///         output: Option<String> = optional("--output"),
+///         /// Sets an input file
+///         input: String = free,
+///     }
+/// }
+///
+/// let mut args = pico_args::Arguments::from_iter(["--output", "out.txt", "in.txt"].iter().cloned());
+/// let app = AppArgs::from_args(&mut args).unwrap();
+/// assert_eq!(app.width, 10);
+/// assert_eq!(app.input, "in.txt");
+/// assert!(AppArgs::HELP.starts_with("  -h, --help\n        Prints help information\n"));
+/// ```
+///
+/// [`Arguments::contains`]: struct.Arguments.html#method.contains
+/// [`Arguments::value_from_str`]: struct.Arguments.html#method.value_from_str
+/// [`Arguments::opt_value_from_str`]: struct.Arguments.html#method.opt_value_from_str
+/// [`Arguments::values_from_str`]: struct.Arguments.html#method.values_from_str
+/// [`Arguments::free_from_str`]: struct.Arguments.html#method.free_from_str
+/// [`FromArguments`]: trait.FromArguments.html
+#[macro_export]
+macro_rules! parse_args {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::parse_args!(@field [$(#[$meta])* $vis struct $name] [] [] [] $($body)*);
+    };
+
+    // A flag.
+    (@field $header:tt [$($fields:tt)*] [$($inits:tt)*] [$($help:tt)*]
+        $(#[doc = $doc:literal])*
+        $field:ident : $ty:ty = flag($k1:literal $(, $k2:literal)?)
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::parse_args!(@field $header
+            [$($fields)* $(#[doc = $doc])* pub $field: $ty,]
+            [$($inits)* { $field: flag, $crate::parse_args!(@keys $k1 $(, $k2)?) }]
+            [$($help)* "  ", $k1, $(", ", $k2,)? "\n", $("       ", $doc, "\n",)*]
+            $($($rest)*)?
+        );
+    };
+
+    // An option.
+    (@field $header:tt [$($fields:tt)*] [$($inits:tt)*] [$($help:tt)*]
+        $(#[doc = $doc:literal])*
+        $field:ident : $ty:ty = $kind:ident($k1:literal $(, $k2:literal)?) $(default $default:expr)?
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::parse_args!(@field $header
+            [$($fields)* $(#[doc = $doc])* pub $field: $ty,]
+            [$($inits)* { $field: $kind, $crate::parse_args!(@keys $k1 $(, $k2)?) $(, $default)? }]
+            [$($help)* "  ", $k1, $(", ", $k2,)? " <", stringify!($field), ">\n", $("       ", $doc, "\n",)*]
+            $($($rest)*)?
+        );
+    };
+
+    // A nested struct.
+    (@field $header:tt [$($fields:tt)*] [$($inits:tt)*] [$($help:tt)*]
+        $(#[doc = $doc:literal])*
+        $field:ident : $ty:ty = flatten
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::parse_args!(@field $header
+            [$($fields)* $(#[doc = $doc])* pub $field: $ty,]
+            [$($inits)* { $field: flatten }]
+            [$($help)*]
+            $($($rest)*)?
+        );
+    };
+
+    // A free-standing argument.
+    (@field $header:tt [$($fields:tt)*] [$($inits:tt)*] [$($help:tt)*]
+        $(#[doc = $doc:literal])*
+        $field:ident : $ty:ty = $kind:ident $(default $default:expr)?
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::parse_args!(@field $header
+            [$($fields)* $(#[doc = $doc])* pub $field: $ty,]
+            [$($inits)* { $field: $kind $(, $default)? }]
+            [$($help)* "  <", stringify!($field), ">\n", $("       ", $doc, "\n",)*]
+            $($($rest)*)?
+        );
+    };
+
+    // All fields are processed.
+    (@field [$($header:tt)*] [$($fields:tt)*] [$({ $field:ident : $($init:tt)* })*] [$($help:tt)*]) => {
+        $($header)* {
+            $($fields)*
+        }
+
+        impl $crate::parse_args!(@name $($header)*) {
+            /// A list of fields with their documentation.
+            #[allow(dead_code)]
+            pub const HELP: &'static str = concat!($($help)*);
+
+            /// Parses arguments.
+            #[allow(dead_code)]
+            pub fn from_args(args: &mut $crate::Arguments) -> Result<Self, $crate::Error> {
+                Ok(Self {
+                    $($field: $crate::parse_args!(@init args, $($init)*),)*
+                })
+            }
+        }
+
+        impl $crate::FromArguments for $crate::parse_args!(@name $($header)*) {
+            fn from_args(args: &mut $crate::Arguments) -> Result<Self, $crate::Error> {
+                // Calls the inherent method.
+                Self::from_args(args)
+            }
+        }
+    };
+
+    (@name $(#[$meta:meta])* $vis:vis struct $name:ident) => { $name };
+
+    (@keys $k1:literal) => { $k1 };
+    (@keys $k1:literal, $k2:literal) => { [$k1, $k2] };
+
+    (@init $args:ident, flag, $keys:expr) => { $args.contains($keys) };
+    (@init $args:ident, value, $keys:expr) => { $args.value_from_str($keys)? };
+    (@init $args:ident, value, $keys:expr, $default:expr) => {
+        $args.opt_value_from_str($keys)?.unwrap_or_else(|| $default)
+    };
+    (@init $args:ident, optional, $keys:expr) => { $args.opt_value_from_str($keys)? };
+    (@init $args:ident, values, $keys:expr) => { $args.values_from_str($keys)? };
+    (@init $args:ident, free) => { $args.free_from_str()? };
+    (@init $args:ident, free, $default:expr) => {
+        $args.opt_free_from_str()?.unwrap_or_else(|| $default)
+    };
+    (@init $args:ident, optional_free) => { $args.opt_free_from_str()? };
+    (@init $args:ident, flatten) => { $crate::FromArguments::from_args($args)? };
+}
+
+/// Parses a command line and checks the result.
+///
+/// The command line is split at whitespace and parsed via [`FromArguments`],
+/// so it works with structs declared by [`parse_args!`]. Quotes are not supported.
+///
+/// - `assert_args!(Type, "args" => { field: value, ... })` checks the listed fields
+///   and evaluates to the parsed value.
+/// - `assert_args!(Type, "args" => Err(kind))` checks that parsing failed
+///   with a specified [`ErrorKind`] and evaluates to the error.
+///
+/// # Panics
+///
+/// - When parsing result is not the expected one.
+///
+/// # Example
+///
+/// ```
+/// use pico_args::{assert_args, ErrorKind};
+///
+/// pico_args::parse_args! {
+///     struct AppArgs {
+///         /// Sets width
+///         width: u32 = value("--width") default 10,
+///         /// Sets an input file
+///         input: String = free,
+///     }
+/// }
+///
+/// assert_args!(AppArgs, "--width 20 in.txt" => { width: 20, input: "in.txt" });
+/// assert_args!(AppArgs, "in.txt" => { width: 10 });
+/// assert_args!(AppArgs, "--width abc in.txt" => Err(ErrorKind::OptionParsingFailed));
+/// ```
+///
+/// [`FromArguments`]: trait.FromArguments.html
+/// [`parse_args!`]: macro.parse_args.html
+/// [`ErrorKind`]: enum.ErrorKind.html
+#[macro_export]
+macro_rules! assert_args {
+    ($ty:ty, $line:expr => Err($kind:expr)) => {{
+        let mut args = $crate::Arguments::from_iter($line.split_whitespace());
+        match args.parse::<$ty>() {
+            Ok(_) => panic!("parsing {:?} must fail", $line),
+            Err(e) => {
+                assert_eq!(e.kind(), $kind, "parsing {:?} failed with '{}'", $line, e);
+                e
+            }
+        }
+    }};
+    ($ty:ty, $line:expr => { $($field:ident : $value:expr),* $(,)? }) => {{
+        let mut args = $crate::Arguments::from_iter($line.split_whitespace());
+        match args.parse::<$ty>() {
+            Ok(parsed) => {
+                $(
+                    assert_eq!(parsed.$field, $value, "the '{}' field of {:?}",
+                               stringify!($field), $line);
+                )*
+                parsed
+            }
+            Err(e) => panic!("failed to parse {:?}: {}", $line, e),
+        }
+    }};
+}
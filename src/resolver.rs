@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{error_to_string, Arguments, Error};
+
+/// A configuration source used by [`Resolver`].
+///
+/// Implemented for `HashMap<String, String>`, so a config file can be parsed
+/// by any means and then passed as a map.
+///
+/// [`Resolver`]: struct.Resolver.html
+pub trait ConfigSource {
+    /// Returns a value by its name, like `width` or `output-dir`.
+    fn get(&self, name: &str) -> Option<String>;
+}
+
+impl ConfigSource for HashMap<String, String> {
+    fn get(&self, name: &str) -> Option<String> {
+        HashMap::get(self, name).cloned()
+    }
+}
+
+/// A layer a value was resolved from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Source {
+    /// A command line option.
+    CommandLine,
+    /// An environment variable.
+    Environment,
+    /// A configuration source.
+    Config,
+    /// A default value.
+    Default,
+}
+
+/// A layered values resolver.
+///
+/// Looks up a value in the command line arguments, then in the environment,
+/// then in the configuration source, reporting which layer it came from.
+///
+/// For the `--output-dir` key and the `APP_` prefix, the environment variable
+/// is `APP_OUTPUT_DIR` and the config name is `output-dir`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use pico_args::{Arguments, Resolver, Source};
+///
+/// let mut config = HashMap::new();
+/// config.insert("height".to_string(), "20".to_string());
+///
+/// let mut args = Arguments::from_iter(["--width", "10"].iter().cloned());
+/// let mut resolver = Resolver::new(&mut args, "APP_").with_config(&config);
+/// assert_eq!(resolver.get::<u32>("--width").unwrap(), Some((10, Source::CommandLine)));
+/// assert_eq!(resolver.get::<u32>("--height").unwrap(), Some((20, Source::Config)));
+/// assert_eq!(resolver.get_or("--depth", 5u32).unwrap(), (5, Source::Default));
+/// ```
+pub struct Resolver<'a> {
+    args: &'a mut Arguments,
+    env_prefix: String,
+    config: Option<&'a dyn ConfigSource>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Creates a new resolver.
+    pub fn new(args: &'a mut Arguments, env_prefix: &str) -> Self {
+        Resolver {
+            args,
+            env_prefix: env_prefix.to_string(),
+            config: None,
+        }
+    }
+
+    /// Sets a configuration source.
+    pub fn with_config(mut self, config: &'a dyn ConfigSource) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Resolves a value by its long key, like `--width`.
+    ///
+    /// Returns `Ok(None)` when the value is not found in any layer.
+    ///
+    /// # Errors
+    ///
+    /// - When a command line option is malformed.
+    /// - When value parsing failed. Errors from all layers are reported via
+    ///   `Error::OptionParsingFailed`.
+    pub fn get<T>(&mut self, key: &'static str) -> Result<Option<(T, Source)>, Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        if let Some(value) = self.args.opt_value_from_str(key)? {
+            return Ok(Some((value, Source::CommandLine)));
+        }
+
+        let name = key.trim_start_matches('-');
+        let env_name = format!("{}{}", self.env_prefix, name.to_uppercase().replace('-', "_"));
+        if let Ok(value) = std::env::var(env_name) {
+            return parse(key, value).map(|v| Some((v, Source::Environment)));
+        }
+
+        if let Some(value) = self.config.and_then(|c| c.get(name)) {
+            return parse(key, value).map(|v| Some((v, Source::Config)));
+        }
+
+        Ok(None)
+    }
+
+    /// Resolves a value by its long key, falling back to a default value.
+    pub fn get_or<T>(&mut self, key: &'static str, default: T) -> Result<(T, Source), Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        Ok(self.get(key)?.unwrap_or((default, Source::Default)))
+    }
+}
+
+fn parse<T>(key: &'static str, value: String) -> Result<T, Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    T::from_str(&value).map_err(|e| Error::OptionParsingFailed {
+        key,
+        value,
+        cause: error_to_string(e),
+    })
+}
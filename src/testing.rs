@@ -0,0 +1,119 @@
+/*!
+Property-testing helpers for parse functions.
+
+Arguments are described as units: a flag, an option with its values or a free-standing argument.
+Units are shuffled with a seeded generator, so failures are reproducible.
+
+```
+use pico_args::testing::assert_order_insensitive;
+
+fn parse(mut args: pico_args::Arguments) -> Result<(bool, u32, String), pico_args::Error> {
+    Ok((args.contains("-v"), args.value_from_str("--width")?, args.free_from_str()?))
+}
+
+assert_order_insensitive(&[&["-v"], &["--width", "10"], &["file"]], parse);
+```
+*/
+
+use std::fmt::Debug;
+
+use crate::Arguments;
+
+/// A small deterministic pseudo-random number generator.
+///
+/// Uses xorshift64*. Not suitable for cryptography.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a new generator.
+    ///
+    /// The same seed produces the same sequence.
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift.
+        Rng(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a random number in `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// - When `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n != 0, "the range must not be empty");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Shuffles items in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Returns a random permutation of units as a flat arguments list.
+///
+/// Units that do not start with `-` are free-standing arguments
+/// and keep their relative order. `-` alone is a free-standing argument as well.
+pub fn permute(units: &[&[&str]], rng: &mut Rng) -> Vec<String> {
+    let mut order: Vec<usize> = (0..units.len()).collect();
+    rng.shuffle(&mut order);
+
+    // Put free-standing arguments back in their original order
+    // into the slots they were shuffled into.
+    let mut free = units.iter().enumerate()
+        .filter(|(_, unit)| !is_keyed(unit))
+        .map(|(i, _)| i);
+    for i in order.iter_mut() {
+        if !is_keyed(units[*i]) {
+            *i = free.next().unwrap_or(*i);
+        }
+    }
+
+    order.iter()
+        .flat_map(|i| units[*i].iter().map(|s| s.to_string()))
+        .collect()
+}
+
+fn is_keyed(unit: &[&str]) -> bool {
+    unit.first().map_or(false, |s| s.starts_with('-') && s.len() > 1)
+}
+
+/// Checks that the parsing result doesn't depend on the order of keyed units.
+///
+/// Runs `parse` on the original order and on 64 random permutations
+/// produced by [`permute`] with a fixed seed.
+///
+/// # Panics
+///
+/// - When any permutation produces a different result.
+///   The message contains the offending arguments.
+///
+/// [`permute`]: fn.permute.html
+pub fn assert_order_insensitive<T, F>(units: &[&[&str]], mut parse: F)
+where
+    T: PartialEq + Debug,
+    F: FnMut(Arguments) -> T,
+{
+    let original: Vec<String> = units.iter().flat_map(|unit| unit.iter().map(|s| s.to_string())).collect();
+    let expected = parse(Arguments::from_iter(original.iter().cloned()));
+
+    let mut rng = Rng::new(1);
+    for _ in 0..64 {
+        let args = permute(units, &mut rng);
+        let actual = parse(Arguments::from_iter(args.iter().cloned()));
+        assert!(actual == expected,
+                "parsing {:?} produced {:?}, while {:?} produced {:?}",
+                args, actual, original, expected);
+    }
+}
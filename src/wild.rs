@@ -0,0 +1,59 @@
+// Wildcard expansion for platforms where the shell doesn't do it, like Windows.
+//
+// Only `*` and `?` in the last path component are supported.
+
+// Returns matching paths in the alphabetical order.
+//
+// Returns `None` when the argument is not a pattern or when nothing matches,
+// so the argument can be kept as is, like shells do.
+pub(crate) fn expand(arg: &str) -> Option<Vec<String>> {
+    let split = arg.rfind(['/', '\\']).map(|idx| idx + 1).unwrap_or(0);
+    let (dir, pattern) = arg.split_at(split);
+    if !pattern.contains(['*', '?']) || dir.contains(['*', '?']) {
+        return None;
+    }
+
+    let entries = std::fs::read_dir(if dir.is_empty() { "." } else { dir }).ok()?;
+    let pattern: Vec<char> = pattern.chars().collect();
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| matches(&pattern, &name.chars().collect::<Vec<_>>()))
+        .map(|name| format!("{}{}", dir, name))
+        .collect();
+
+    if paths.is_empty() {
+        return None;
+    }
+
+    paths.sort();
+    Some(paths)
+}
+
+// Matches a name against a pattern with `*` and `?`, ignoring ASCII case.
+//
+// `?` matches a single character.
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The last `*` position in the pattern and the name position it was matched at.
+    let mut star = None;
+    while n < name.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if p < pattern.len()
+            && (pattern[p] == '?' || pattern[p].eq_ignore_ascii_case(&name[n]))
+        {
+            p += 1;
+            n += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` consume one more character.
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, n));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
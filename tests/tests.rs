@@ -3,8 +3,17 @@ use std::ffi::OsString;
 
 use pico_args::*;
 
-fn to_vec(args: &[&str]) -> Vec<OsString> {
-    args.iter().map(|s| s.to_string().into()).collect()
+#[cfg(not(feature = "utf8-only"))]
+type Arg = OsString;
+#[cfg(not(feature = "utf8-only"))]
+type ArgStr = std::ffi::OsStr;
+#[cfg(feature = "utf8-only")]
+type Arg = String;
+#[cfg(feature = "utf8-only")]
+type ArgStr = str;
+
+fn to_vec(args: &[&str]) -> Vec<Arg> {
+    args.iter().map(|s| Arg::from(*s)).collect()
 }
 
 #[test]
@@ -148,10 +157,11 @@ fn eq_option_err_02() {
 #[cfg(feature = "eq-separator")]
 #[test]
 fn eq_option_err_03() {
+    // An empty quoted value is a value, which cannot be parsed as a number.
     let mut args = Arguments::from_vec(to_vec(&["-w=''"]));
     let value: Result<Option<u32>, Error> = args.opt_value_from_str("-w");
     assert_eq!(value.unwrap_err().to_string(),
-               "the '-w' option doesn't have an associated value");
+               "failed to parse '-w ': cannot parse integer from empty string");
 }
 
 #[cfg(feature = "eq-separator")]
@@ -186,7 +196,7 @@ fn eq_option_err_07() {
     let mut args = Arguments::from_vec(to_vec(&["-w=a"]));
     let value: Result<Option<u32>, Error> = args.opt_value_from_str("-w");
     assert_eq!(value.unwrap_err().to_string(),
-               "failed to parse 'a': invalid digit found in string");
+               "failed to parse '-w a': invalid digit found in string");
 }
 
 #[cfg(not(any(feature = "eq-separator", feature = "short-space-opt")))]
@@ -227,7 +237,7 @@ fn combined_flags_repeated_02() {
 fn combined_flags_leftover() {
     let mut args = Arguments::from_vec(to_vec(&["-ab"]));
     assert!(args.contains("-a"));
-    assert_eq!(args.finish(), vec![OsString::from("-b")]);
+    assert_eq!(args.finish(), vec![Arg::from("-b")]);
 }
 
 #[test]
@@ -335,6 +345,7 @@ fn duplicated_options_01() {
     assert_eq!(value2.unwrap(), "test2");
 }
 
+#[cfg(not(feature = "utf8-only"))]
 #[test]
 fn option_from_os_str_01() {
     use std::path::PathBuf;
@@ -348,6 +359,14 @@ fn option_from_os_str_01() {
     assert_eq!(value.unwrap().unwrap().display().to_string(), "text.txt");
 }
 
+#[test]
+fn option_parsing_failed_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "a"]));
+    let value: Result<Option<u32>, Error> = args.opt_value_from_str(["-w", "--width"]);
+    assert_eq!(value.unwrap_err().to_string(),
+               "failed to parse '--width a': invalid digit found in string");
+}
+
 #[test]
 fn missing_option_value_01() {
     let mut args = Arguments::from_vec(to_vec(&["--value"]));
@@ -362,7 +381,7 @@ fn missing_option_value_02() {
     let value: Result<Option<u32>, Error> = args.opt_value_from_str("--value");
     assert!(value.is_err()); // ignore error
     // the `--value` flag should not be removed by the previous command
-    assert_eq!(args.finish(), vec![OsString::from("--value")]);
+    assert_eq!(args.finish(), vec![Arg::from("--value")]);
 }
 
 #[test]
@@ -371,7 +390,7 @@ fn missing_option_value_03() {
     let value: Result<Option<u32>, Error> = args.opt_value_from_str("--value");
     assert!(value.is_err()); // ignore error
     // the `--value` flag should not be removed by the previous command
-    assert_eq!(args.finish(), vec![OsString::from("--value"), OsString::from("q")]);
+    assert_eq!(args.finish(), vec![Arg::from("--value"), Arg::from("q")]);
 }
 
 #[test]
@@ -437,6 +456,7 @@ fn opt_free_from_fn_05() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn opt_free_from_fn_06() {
     let mut args = Arguments::from_vec(to_vec(&["-3.14"]));
     assert_eq!(args.opt_free_from_fn(f32::from_str).unwrap(), Some(-3.14f32));
@@ -493,3 +513,2042 @@ fn subcommand() {
     let cmd = args.subcommand().unwrap();
     assert_eq!(cmd, None);
 }
+
+#[cfg(feature = "color")]
+#[test]
+fn colored_error_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "a"]));
+    let err = args.value_from_str::<_, u32>("--width").unwrap_err();
+
+    std::env::remove_var("NO_COLOR");
+    assert_eq!(err.display_colored().to_string(),
+               "failed to parse '\x1b[33m--width\x1b[0m \x1b[31ma\x1b[0m': \
+                invalid digit found in string");
+
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(err.display_colored().to_string(), err.to_string());
+}
+
+#[test]
+fn fits_in_os_limits_01() {
+    assert!(fits_in_os_limits(&[OsString::from("app"), OsString::from("--width")]));
+
+    let huge: Vec<OsString> = (0..100_000).map(|i| format!("file{:05}.txt", i).into()).collect();
+    assert!(!fits_in_os_limits(&huge));
+}
+
+#[test]
+fn write_argfile_01() {
+    let path = std::env::temp_dir().join("pico-args-write-argfile-01.txt");
+    let args = [OsString::from("--name"), OsString::from("a \"b\""), OsString::from("c\\d")];
+    let arg = write_argfile(&path, &args).unwrap();
+
+    let mut expected = OsString::from("@");
+    expected.push(&path);
+    assert_eq!(arg, expected);
+
+    let data = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(data, "\"--name\"\n\"a \\\"b\\\"\"\n\"c\\\\d\"\n");
+}
+
+#[test]
+fn handle_common_flags_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--version", "-h"]));
+    assert_eq!(args.handle_common_flags("", ""), Some(CommonFlag::Help));
+    assert_eq!(args.handle_common_flags("", ""), Some(CommonFlag::Version));
+    assert_eq!(args.handle_common_flags("", ""), None);
+}
+
+#[cfg(feature = "utf8-only")]
+#[test]
+fn utf8_only_01() {
+    let mut args = Arguments::from_vec(vec!["--width".to_string(), "10".to_string(), "a".to_string()]);
+    let value: u32 = args.value_from_str("--width").unwrap();
+    assert_eq!(value, 10);
+    assert_eq!(args.finish(), vec!["a".to_string()]);
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn eq_option_escaped_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--name=\"a \\\"b\\\"\""]));
+    let value: String = args.value_from_str("--name").unwrap();
+    assert_eq!(value, "a \"b\"");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn eq_option_quoted_empty() {
+    let mut args = Arguments::from_vec(to_vec(&["--name=\"\"", "--path=''", "--x="]));
+    let value: String = args.value_from_str("--name").unwrap();
+    assert_eq!(value, "");
+    assert_eq!(args.value_str("--path").unwrap(), "");
+    assert_eq!(args.value_from_str::<_, String>("--x").unwrap_err(),
+               Error::OptionWithoutAValue("--x"));
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn eq_option_quoted_trailing_backslash() {
+    let mut args = Arguments::from_vec(to_vec(&["--out=\"C:\\temp\\\""]));
+    let value: String = args.value_from_str("--out").unwrap();
+    assert_eq!(value, "C:\\temp\\");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn eq_option_quoted_inner_quote() {
+    let mut args = Arguments::from_vec(to_vec(&["--x=\"a\"b\""]));
+    let value: String = args.value_from_str("--x").unwrap();
+    assert_eq!(value, "a\"b");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn eq_option_escaped_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--path='C:\\dir'"]));
+    let value: String = args.value_from_str("--path").unwrap();
+    assert_eq!(value, "C:\\dir");
+}
+
+#[test]
+fn parse_eq_value_01() {
+    assert_eq!(parse_eq_value("10").unwrap(), "10");
+    assert_eq!(parse_eq_value("10'").unwrap(), "10'");
+    assert_eq!(parse_eq_value("'10'").unwrap(), "10");
+    assert_eq!(parse_eq_value("\"10\"").unwrap(), "10");
+    assert_eq!(parse_eq_value("'\"10\"'").unwrap(), "\"10\"");
+    assert_eq!(parse_eq_value("'a\\'b'").unwrap(), "a\\'b");
+    assert_eq!(parse_eq_value("'a\\\\b'").unwrap(), "a\\\\b");
+    assert_eq!(parse_eq_value("\"a\\\"b\"").unwrap(), "a\"b");
+    assert_eq!(parse_eq_value("\"a\\\\\"").unwrap(), "a\\");
+    assert_eq!(parse_eq_value("\"a\\nb\"").unwrap(), "a\\nb");
+    assert_eq!(parse_eq_value("\"a\\\"").unwrap(), "a\\");
+    assert_eq!(parse_eq_value("\"a\"b\"").unwrap(), "a\"b");
+    assert_eq!(parse_eq_value("\"\"").unwrap(), "");
+    assert_eq!(parse_eq_value("''").unwrap(), "");
+}
+
+#[test]
+fn parse_eq_value_02() {
+    assert_eq!(parse_eq_value(""), None);
+    assert_eq!(parse_eq_value("'"), None);
+    assert_eq!(parse_eq_value("\""), None);
+    assert_eq!(parse_eq_value("'10\""), None);
+    assert_eq!(parse_eq_value("\"10"), None);
+}
+
+#[test]
+fn parse_eq_value_roundtrip() {
+    // Single-quoted values are taken verbatim, double-quoted ones are escaped.
+    fn quote(s: &str, q: char) -> String {
+        let mut quoted = String::new();
+        quoted.push(q);
+        for c in s.chars() {
+            if q == '"' && (c == '"' || c == '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push(q);
+        quoted
+    }
+
+    // Every combination of "interesting" characters up to length 4.
+    let alphabet = ['a', ' ', '"', '\'', '\\', '=', 'ж'];
+    let mut inputs = vec![String::new()];
+    for _ in 0..4 {
+        let mut next = Vec::new();
+        for s in &inputs {
+            for c in &alphabet {
+                let mut s = s.clone();
+                s.push(*c);
+                next.push(s);
+            }
+        }
+        inputs.extend(next);
+        inputs.dedup();
+    }
+
+    for s in &inputs {
+        for &q in &['"', '\''] {
+            assert_eq!(parse_eq_value(&quote(s, q)).unwrap(), s.as_str(), "{}", quote(s, q));
+        }
+    }
+}
+
+#[test]
+fn parse_eq_value_fuzz() {
+    // Every combination of "interesting" characters up to length 5 must not panic
+    // and must follow the grammar.
+    let alphabet = ['a', '"', '\'', '\\', 'ж'];
+    let mut inputs = vec![String::new()];
+    let mut last = inputs.clone();
+    for _ in 0..5 {
+        let mut next = Vec::new();
+        for s in &last {
+            for c in &alphabet {
+                let mut s = s.clone();
+                s.push(*c);
+                next.push(s);
+            }
+        }
+        inputs.extend(next.iter().cloned());
+        last = next;
+    }
+
+    for s in &inputs {
+        let value = parse_eq_value(s);
+        match s.chars().next() {
+            None => assert_eq!(value, None),
+            Some(q) if q == '"' || q == '\'' => {
+                let closed = s.chars().count() > 1 && s.ends_with(q);
+                assert_eq!(value.is_some(), closed, "{}", s);
+                if let Some(value) = value {
+                    assert!(value.len() <= s.len() - 2, "{}", s);
+                    if q == '\'' {
+                        assert_eq!(value, &s[1..s.len() - 1], "{}", s);
+                    }
+                }
+            }
+            Some(_) => assert_eq!(value.unwrap(), s.as_str()),
+        }
+    }
+}
+
+#[test]
+fn subcommand_among_01() {
+    let mut args = Arguments::from_vec(to_vec(&["create", "--help"]));
+    let cmd = args.subcommand_among(&["create", "extract", "list"]).unwrap();
+    assert_eq!(cmd, Some("create".to_string()));
+    assert_eq!(args.subcommand_among(&["create"]).unwrap(), None);
+}
+
+#[test]
+fn subcommand_among_02() {
+    let mut args = Arguments::from_vec(to_vec(&["craete"]));
+    let err = args.subcommand_among(&["create", "extract", "list"]).unwrap_err();
+    assert_eq!(err.to_string(), "unknown subcommand 'craete', did you mean 'create'?");
+    // Not removed.
+    assert_eq!(args.finish(), to_vec(&["craete"]));
+}
+
+#[test]
+fn subcommand_among_03() {
+    let mut args = Arguments::from_vec(to_vec(&["build"]));
+    let err = args.subcommand_among(&["create", "extract", "list"]).unwrap_err();
+    assert_eq!(err.to_string(), "unknown subcommand 'build'");
+}
+
+#[test]
+fn prelude_types_are_send_sync() {
+    use pico_args::prelude::*;
+
+    fn assert_traits<T: Clone + std::fmt::Debug + Send + Sync>() {}
+    assert_traits::<Arguments>();
+    assert_traits::<CommonFlag>();
+    assert_traits::<Error>();
+    assert_traits::<Keys>();
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Build,
+    Run,
+}
+
+impl FromStr for Command {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "build" => Ok(Command::Build),
+            "run" => Ok(Command::Run),
+            _ => Err("unknown command"),
+        }
+    }
+}
+
+#[test]
+fn subcommand_from_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&["run", "build", "--help"]));
+    assert_eq!(args.subcommand_from_str().unwrap(), Some(Command::Run));
+    assert_eq!(args.subcommand_from_str().unwrap(), Some(Command::Build));
+    assert_eq!(args.subcommand_from_str::<Command>().unwrap(), None);
+}
+
+#[test]
+fn subcommand_from_str_02() {
+    let mut args = Arguments::from_vec(to_vec(&["test"]));
+    let err = args.subcommand_from_str::<Command>().unwrap_err();
+    assert_eq!(err.to_string(), "failed to parse 'test': unknown command");
+    assert_eq!(args.finish(), to_vec(&["test"]));
+}
+
+#[test]
+fn values_from_str_map_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-D", "A=1", "--other", "-D", "B=x=y", "-D", "C="]));
+    let defs = args.values_from_str_map("-D").unwrap();
+    assert_eq!(defs, vec![
+        ("A".to_string(), "1".to_string()),
+        ("B".to_string(), "x=y".to_string()),
+        ("C".to_string(), String::new()),
+    ]);
+}
+
+#[test]
+fn values_from_str_map_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--env", "A"]));
+    let err = args.values_from_str_map("--env").unwrap_err();
+    assert_eq!(err.to_string(), "failed to parse '--env A': expected a NAME=VALUE pair");
+}
+
+#[test]
+fn last_value_from_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--color", "always", "-v", "--color", "never"]));
+    let value: String = args.last_value_from_str("--color").unwrap();
+    assert_eq!(value, "never");
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+}
+
+#[test]
+fn last_value_from_str_02() {
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    let value: Result<u32, Error> = args.last_value_from_str("--width");
+    assert_eq!(value.unwrap_err().to_string(), "the '--width' option must be set");
+}
+
+#[test]
+fn opt_last_value_from_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-w", "10", "-w", "20"]));
+    let value: Option<u32> = args.opt_last_value_from_str("-w").unwrap();
+    assert_eq!(value, Some(20));
+    let value: Option<u32> = args.opt_last_value_from_str("-w").unwrap();
+    assert_eq!(value, None);
+}
+
+#[test]
+fn values_tuple_from_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--size", "10", "20", "file"]));
+    let size: (u32, u32) = args.values_tuple_from_str("--size").unwrap();
+    assert_eq!(size, (10, 20));
+    assert_eq!(args.finish(), to_vec(&["-v", "file"]));
+}
+
+#[test]
+fn values_tuple_from_str_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--size", "10", "a"]));
+    let size: Result<(u32, u32), Error> = args.values_tuple_from_str("--size");
+    assert_eq!(size.unwrap_err().to_string(),
+               "failed to parse '--size a': value 2 of 2: invalid digit found in string");
+    assert_eq!(args.finish(), to_vec(&["--size", "10", "a"]));
+}
+
+#[test]
+fn values_tuple_from_str_03() {
+    let mut args = Arguments::from_vec(to_vec(&["--size", "10"]));
+    let size: Result<(u32, u32), Error> = args.values_tuple_from_str("--size");
+    assert_eq!(size.unwrap_err().to_string(),
+               "the '--size' option doesn't have an associated value");
+}
+
+#[test]
+fn opt_values_tuple_from_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    let value: Option<(u32, String, f32)> = args.opt_values_tuple_from_str("--geometry").unwrap();
+    assert_eq!(value, None);
+}
+
+#[cfg(all(unix, not(feature = "utf8-only")))]
+#[test]
+fn subcommand_os_01() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let name = OsString::from_vec(vec![b'f', 0xff, b'o']);
+    let mut args = Arguments::from_vec(vec![name.clone(), "-v".into()]);
+    assert_eq!(args.subcommand_os(), Some(name));
+    assert_eq!(args.subcommand_os(), None);
+}
+
+#[cfg(all(unix, not(feature = "utf8-only")))]
+#[test]
+fn subcommand_lossy_01() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let mut args = Arguments::from_vec(vec![OsString::from_vec(vec![b'f', 0xff, b'o'])]);
+    assert_eq!(args.subcommand_lossy(), Some("f\u{FFFD}o".to_string()));
+}
+
+#[test]
+fn from_iter_01() {
+    let mut args = Arguments::from_iter(["-w", "10"].iter().cloned());
+    let value: u32 = args.value_from_str("-w").unwrap();
+    assert_eq!(value, 10);
+}
+
+#[test]
+fn from_iter_02() {
+    let args: Arguments = vec!["a".to_string(), "b".to_string()].into_iter().collect();
+    assert_eq!(args.finish(), to_vec(&["a", "b"]));
+}
+
+#[test]
+fn from_windows_cmdline_01() {
+    let line = r#""C:\Program Files\app.exe" -w 10 "a b" c\d "e\"f" g\\"h i" j\\\"k "" "l""m""#;
+    let args = Arguments::from_windows_cmdline(line);
+    assert_eq!(args.finish(), to_vec(&[
+        "-w", "10", "a b", "c\\d", "e\"f", "g\\h i", "j\\\"k", "", "l\"m",
+    ]));
+}
+
+#[test]
+fn from_windows_cmdline_02() {
+    let args = Arguments::from_windows_cmdline("app.exe");
+    assert_eq!(args.finish(), to_vec(&[]));
+
+    let args = Arguments::from_windows_cmdline("");
+    assert_eq!(args.finish(), to_vec(&[]));
+
+    let args = Arguments::from_windows_cmdline("app.exe \t -v\t");
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+}
+
+#[test]
+fn builder_01() {
+    let cli = builder::Cli::new("app")
+        .flag(["-h", "--help"], "Prints help")
+        .opt(["-w", "--width"], "WIDTH", "Sets width")
+        .positional("INPUT", "Input file");
+
+    let mut args = Arguments::from_vec(to_vec(&["in.txt", "--width", "10", "-h", "rest"]));
+    let matches = cli.parse(&mut args).unwrap();
+    assert!(matches.contains("-h"));
+    assert!(matches.contains("--help"));
+    assert_eq!(matches.value("-w"), Some("10"));
+    assert_eq!(matches.value_from_str::<u32>("--width").unwrap(), 10);
+    assert_eq!(matches.opt_value_from_str::<u32>("--width").unwrap(), Some(10));
+    assert_eq!(matches.positional("INPUT"), Some("in.txt"));
+    assert_eq!(args.finish(), to_vec(&["rest"]));
+}
+
+#[test]
+fn builder_02() {
+    let cli = builder::Cli::new("app")
+        .flag("--verbose", "Verbose output")
+        .opt("--width", "WIDTH", "Sets width");
+
+    let mut args = Arguments::from_vec(to_vec(&["--width", "abc"]));
+    let matches = cli.parse(&mut args).unwrap();
+    assert!(!matches.contains("--verbose"));
+    assert_eq!(matches.value_from_str::<u32>("--width").unwrap_err().to_string(),
+               "failed to parse '--width abc': invalid digit found in string");
+}
+
+#[test]
+fn builder_required_value() {
+    let cli = builder::Cli::new("app").opt(["-w", "--width"], "WIDTH", "Sets width");
+
+    let mut args = Arguments::from_vec(Vec::new());
+    let matches = cli.parse(&mut args).unwrap();
+    assert_eq!(matches.opt_value_from_str::<u32>("-w").unwrap(), None);
+    assert_eq!(matches.value_from_str::<u32>("-w").unwrap_err().to_string(),
+               "the '-w/--width' option must be set");
+}
+
+#[test]
+fn compat_01() {
+    use pico_args::compat::{App, Arg};
+
+    let app = App::new("app")
+        .arg(Arg::new("verbose").short("-v").long("--verbose"))
+        .arg(Arg::new("width").short("-w").long("--width").takes_value(true).default_value("10"))
+        .arg(Arg::new("height").long("--height").takes_value(true).required(true))
+        .arg(Arg::new("INPUT"));
+
+    let mut args = Arguments::from_vec(to_vec(&["--height", "5", "in.txt", "-v", "rest"]));
+    let matches = app.try_get_matches_from(&mut args).unwrap();
+    assert!(matches.is_present("verbose"));
+    assert_eq!(matches.value_of("verbose"), None);
+    assert!(!matches.is_present("width"));
+    assert_eq!(matches.value_of("width"), Some("10"));
+    assert_eq!(matches.value_of("height"), Some("5"));
+    assert_eq!(matches.value_of("INPUT"), Some("in.txt"));
+    assert_eq!(matches.value_of("unknown"), None);
+    assert_eq!(args.finish(), to_vec(&["rest"]));
+}
+
+#[test]
+fn compat_02() {
+    use pico_args::compat::{App, Arg};
+
+    let app = App::new("app")
+        .arg(Arg::new("height").long("--height").takes_value(true).required(true));
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    assert_eq!(app.try_get_matches_from(&mut args).unwrap_err().to_string(),
+               "the '--height' option must be set");
+
+    let app = App::new("app").arg(Arg::new("INPUT").required(true));
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    assert_eq!(app.try_get_matches_from(&mut args).unwrap_err(), Error::MissingArgument);
+}
+
+#[test]
+fn builder_help() {
+    let cli = builder::Cli::new("app")
+        .about("Does things")
+        .flag(["-h", "--help"], "Prints help")
+        .opt("--width", "WIDTH", "Sets width")
+        .positional("INPUT", "Input file");
+
+    assert_eq!(cli.help(), "\
+app - Does things
+
+USAGE:
+  app [OPTIONS] <INPUT>
+
+FLAGS:
+  -h, --help     Prints help
+
+OPTIONS:
+  --width WIDTH  Sets width
+
+ARGS:
+  <INPUT>        Input file
+");
+}
+
+#[test]
+fn builder_man() {
+    let cli = builder::Cli::new("app")
+        .about("Does things")
+        .flag(["-h", "--help"], "Prints help")
+        .opt("--width", "WIDTH", "Sets width")
+        .positional("INPUT", ".txt or .md file");
+
+    assert_eq!(cli.to_man(), r"
+.TH APP 1
+.SH NAME
+app \- Does things
+.SH SYNOPSIS
+.B app
+[OPTIONS] \fIINPUT\fR
+.SH OPTIONS
+.TP
+\fB\-h\fR, \fB\-\-help\fR
+Prints help
+.TP
+\fB\-\-width\fR \fIWIDTH\fR
+Sets width
+.SH ARGUMENTS
+.TP
+\fIINPUT\fR
+\&.txt or .md file
+"[1..]);
+}
+
+#[test]
+fn trailing_var_arg_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-p", "22", "host", "-v", "-p", "1"]));
+    args.set_trailing_var_arg(true);
+    let port: Option<u32> = args.opt_value_from_str("-p").unwrap();
+    assert_eq!(port, Some(22));
+    assert!(!args.contains("-v"));
+    let port: Option<u32> = args.opt_value_from_str("-p").unwrap();
+    assert_eq!(port, None);
+    let host: String = args.free_from_str().unwrap();
+    assert_eq!(host, "host");
+    assert_eq!(args.finish(), to_vec(&["-v", "-p", "1"]));
+}
+
+#[test]
+fn trailing_var_arg_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--", "-x"]));
+    args.set_trailing_var_arg(true);
+    assert!(args.contains("-v"));
+    assert!(!args.contains("-x"));
+    assert_eq!(args.finish(), to_vec(&["--", "-x"]));
+}
+
+#[test]
+fn trailing_var_arg_disabled() {
+    let mut args = Arguments::from_vec(to_vec(&["host", "-v"]));
+    assert!(args.contains("-v"));
+    assert_eq!(args.finish(), to_vec(&["host"]));
+}
+
+#[test]
+fn opt_free_skip_flags_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--quiet", "file.txt", "-x"]));
+    let value: Option<String> = args.opt_free_skip_flags_from_str().unwrap();
+    assert_eq!(value, Some("file.txt".to_string()));
+    assert!(args.contains("--quiet"));
+    let value: Option<String> = args.opt_free_skip_flags_from_str().unwrap();
+    assert_eq!(value, None);
+    assert_eq!(args.finish(), to_vec(&["-v", "-x"]));
+}
+
+#[test]
+fn opt_free_skip_flags_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "abc"]));
+    let value: Result<Option<u32>, Error> = args.opt_free_skip_flags_from_str();
+    assert_eq!(value.unwrap_err().to_string(),
+               "failed to parse 'abc': invalid digit found in string");
+    assert_eq!(args.finish(), to_vec(&["-v", "abc"]));
+}
+
+#[test]
+fn closure_parser_01() {
+    let base = std::path::PathBuf::from("/base");
+    let mut args = Arguments::from_vec(to_vec(&["--file", "a.txt", "b.txt"]));
+    let file = args.value_from_fn("--file", |s| -> Result<_, String> { Ok(base.join(s)) }).unwrap();
+    assert_eq!(file, std::path::PathBuf::from("/base/a.txt"));
+    let free = args.free_from_fn(|s| -> Result<_, String> { Ok(base.join(s)) }).unwrap();
+    assert_eq!(free, std::path::PathBuf::from("/base/b.txt"));
+}
+
+#[test]
+fn closure_parser_02() {
+    let mut count = 0;
+    let mut args = Arguments::from_vec(to_vec(&["-i", "1", "-i", "2"]));
+    let values: Vec<u32> = args.values_from_fn("-i", |s| {
+        count += 1;
+        s.parse::<u32>()
+    }).unwrap();
+    assert_eq!(values, vec![1, 2]);
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn value_str_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--mode", "fast", "file"]));
+    assert_eq!(args.value_str("--mode").unwrap(), "fast");
+    assert_eq!(args.opt_value_str("--mode").unwrap(), None);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn value_str_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--mode"]));
+    assert_eq!(args.value_str("--mode").unwrap_err().to_string(),
+               "the '--mode' option doesn't have an associated value");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn value_str_eq() {
+    let mut args = Arguments::from_vec(to_vec(&["--mode='fast'"]));
+    let value = args.value_str("--mode").unwrap();
+    assert!(matches!(value, std::borrow::Cow::Borrowed("fast")));
+    assert_eq!(args.finish(), to_vec(&[]));
+}
+
+#[test]
+fn arguments_ref_01() {
+    let raw = to_vec(&["-v", "--width", "10", "file", "-x"]);
+    let mut args = ArgumentsRef::new(&raw);
+    assert!(args.contains("-v"));
+    assert!(!args.contains("-v"));
+    let width: u32 = args.value_from_str("--width").unwrap();
+    assert_eq!(width, 10);
+    assert_eq!(args.opt_free_str().unwrap(), Some("file"));
+    assert_eq!(args.finish(), vec![raw[4].as_ref() as &ArgStr]);
+}
+
+#[test]
+fn arguments_ref_02() {
+    let raw = to_vec(&["--width", "-v", "--height"]);
+    let mut args = ArgumentsRef::new(&raw);
+    assert!(args.contains("-v"));
+    assert_eq!(args.opt_value_str("--width").unwrap().unwrap(), "--height");
+    assert_eq!(args.value_from_str::<_, u32>("--depth").unwrap_err().to_string(),
+               "the '--depth' option must be set");
+    assert!(args.finish().is_empty());
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn arguments_ref_eq() {
+    let raw = to_vec(&["--width=10", "--name='a b'"]);
+    let mut args = ArgumentsRef::new(&raw);
+    assert_eq!(args.opt_value_str("--name").unwrap().unwrap(), "a b");
+    assert_eq!(args.opt_value_from_str::<_, u32>("--width").unwrap(), Some(10));
+}
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    Args(String),
+    BadLevel(u8),
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AppError::Args(e) => write!(f, "{}", e),
+            AppError::BadLevel(n) => write!(f, "level {} is too high", n),
+        }
+    }
+}
+
+impl From<TryError<AppError>> for AppError {
+    fn from(e: TryError<AppError>) -> Self {
+        match e {
+            TryError::Args(e) => AppError::Args(e.to_string()),
+            TryError::Value(e) => e,
+        }
+    }
+}
+
+fn parse_level(s: &str) -> Result<u8, AppError> {
+    match s.parse() {
+        Ok(n) if n <= 3 => Ok(n),
+        Ok(n) => Err(AppError::BadLevel(n)),
+        Err(_) => Err(AppError::Args(s.to_string())),
+    }
+}
+
+#[test]
+fn try_value_from_fn_01() {
+    fn run(args: &[&str]) -> Result<u8, AppError> {
+        let mut args = Arguments::from_vec(to_vec(args));
+        Ok(args.try_value_from_fn("--level", parse_level)?)
+    }
+
+    assert_eq!(run(&["--level", "2"]), Ok(2));
+    assert_eq!(run(&["--level", "5"]), Err(AppError::BadLevel(5)));
+    assert_eq!(run(&[]), Err(AppError::Args("the '--level' option must be set".to_string())));
+    assert_eq!(run(&["--level"]),
+               Err(AppError::Args("the '--level' option doesn't have an associated value".to_string())));
+}
+
+#[test]
+fn try_opt_value_from_fn_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--level", "5"]));
+    let value = args.try_opt_value_from_fn("--level", parse_level);
+    match value {
+        Err(TryError::Value(e)) => assert_eq!(e, AppError::BadLevel(5)),
+        _ => panic!("unexpected result"),
+    }
+    // Not removed on error.
+    assert_eq!(args.finish(), to_vec(&["--level", "5"]));
+}
+
+#[test]
+fn keys_accessors() {
+    let keys = Keys::from(["-w", "--width"]);
+    assert_eq!(keys.first(), "-w");
+    assert_eq!(keys.second(), "--width");
+    assert_eq!(keys.iter().collect::<Vec<_>>(), vec!["-w", "--width"]);
+    assert_eq!(keys.to_string(), "-w/--width");
+
+    let keys = Keys::from("--width");
+    assert_eq!(keys.second(), "");
+    assert_eq!(keys.iter().collect::<Vec<_>>(), vec!["--width"]);
+    assert_eq!(keys.to_string(), "--width");
+}
+
+#[test]
+fn keys_from_error() {
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    match args.value_from_str::<_, u32>(["-w", "--width"]) {
+        Err(Error::MissingOption(keys)) => assert_eq!(keys.second(), "--width"),
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[test]
+fn error_accessors() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "abc"]));
+    let e = args.value_from_str::<_, u32>(["-w", "--width"]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::OptionParsingFailed);
+    assert_eq!(e.key(), Some("--width"));
+    assert_eq!(e.value(), Some("abc"));
+    assert_eq!(e.cause(), Some("invalid digit found in string"));
+
+    let e = args.value_from_str::<_, u32>(["-h", "--height"]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::MissingOption);
+    assert_eq!(e.key(), Some("--height"));
+    assert_eq!(e.keys().map(|k| k.first()), Some("-h"));
+    assert_eq!(e.value(), None);
+}
+
+#[test]
+fn error_accessors_subcommand() {
+    let mut args = Arguments::from_vec(to_vec(&["biuld"]));
+    let e = args.subcommand_among(&["build", "test"]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnknownSubcommand);
+    assert_eq!(e.value(), Some("biuld"));
+    assert_eq!(e.suggestion(), Some("build"));
+    assert_eq!(e.cause(), None);
+}
+
+#[test]
+fn error_eq() {
+    let mut args = Arguments::from_vec(to_vec(&["--width"]));
+    let e = args.value_from_str::<_, u32>("--width").unwrap_err();
+    assert_eq!(e, Error::OptionWithoutAValue("--width"));
+    assert_ne!(e, Error::OptionWithoutAValue("--height"));
+
+    let e = args.value_from_str::<_, u32>(["-h", "--height"]).unwrap_err();
+    assert_eq!(e, Error::MissingOption(Keys::from(["-h", "--height"])));
+}
+
+#[test]
+fn const_keys() {
+    const WIDTH: Keys = Keys::short_long("-w", "--width");
+    let mut args = Arguments::from_vec(to_vec(&["--width", "10"]));
+    let value: u32 = args.value_from_str(WIDTH).unwrap();
+    assert_eq!(value, 10);
+}
+
+#[test]
+#[should_panic]
+fn const_keys_invalid() {
+    Keys::short_long("--w", "--width");
+}
+
+#[test]
+fn const_keys_repeated() {
+    const VERBOSE: Keys = Keys::short_long("-vv", "--very-verbose");
+    const UNICODE: Keys = Keys::short_long("-жж", "--zh");
+    assert_eq!(VERBOSE.first(), "-vv");
+    assert_eq!(UNICODE.first(), "-жж");
+}
+
+#[test]
+#[should_panic(expected = "a single character or a repeated character")]
+fn const_keys_mixed_short() {
+    Keys::short_long("-wx", "--width");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn quote_mode_verbatim() {
+    let mut args = Arguments::from_vec(to_vec(&["--pattern='\"quoted\"'", "--name=\"a\"b\"", "--empty="]));
+    args.set_quote_mode(QuoteMode::Verbatim);
+    let value: String = args.value_from_str("--pattern").unwrap();
+    assert_eq!(value, "'\"quoted\"'");
+    let value: String = args.value_from_str("--name").unwrap();
+    assert_eq!(value, "\"a\"b\"");
+    assert_eq!(args.value_from_str::<_, String>("--empty").unwrap_err(),
+               Error::OptionWithoutAValue("--empty"));
+}
+
+#[cfg(all(unix, not(feature = "utf8-only")))]
+#[test]
+fn non_utf8_option_like_01() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let bad = OsString::from_vec(vec![b'-', b'-', b'b', 0xff]);
+    let mut args = Arguments::from_vec(vec![bad.clone(), "file".into()]);
+    assert_eq!(args.subcommand().unwrap(), None);
+    let value: Option<String> = args.opt_free_skip_flags_from_str().unwrap();
+    assert_eq!(value, Some("file".to_string()));
+    assert_eq!(args.finish(), vec![bad]);
+}
+
+#[cfg(all(unix, not(feature = "utf8-only")))]
+#[test]
+fn non_utf8_option_like_02() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let bad = OsString::from_vec(vec![b'-', b'x', 0xff]);
+    let mut args = Arguments::from_vec(vec!["-v".into(), bad.clone(), "host".into(), "-v".into()]);
+    args.set_trailing_var_arg(true);
+    assert!(args.contains("-v"));
+    assert!(!args.contains("-v"));
+    assert_eq!(args.finish(), vec![bad, "host".into(), "-v".into()]);
+}
+
+#[test]
+fn consumed_positions_01() {
+    let mut args = Arguments::from_vec(to_vec(&["file", "-v", "--width", "10", "rest"]));
+    let width: u32 = args.value_from_str("--width").unwrap();
+    assert_eq!(width, 10);
+    assert!(args.contains("-v"));
+    let file: String = args.free_from_str().unwrap();
+    assert_eq!(file, "file");
+    assert_eq!(args.consumed(), &[2, 3, 1, 0]);
+    assert_eq!(args.positions(), &[4]);
+}
+
+#[test]
+fn consumed_positions_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "abc"]));
+    assert!(args.value_from_str::<_, u32>("--width").is_err());
+    assert!(args.consumed().is_empty());
+    assert_eq!(args.positions(), &[0, 1]);
+}
+
+#[test]
+fn indexed_values_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-I", "a", "-L", "x", "-I", "b", "file"]));
+    let includes: Vec<(usize, String)> = args.indexed_values_from_str("-I").unwrap();
+    let libs: Vec<(usize, String)> = args.indexed_values_from_str("-L").unwrap();
+    assert_eq!(includes, vec![(0, "a".to_string()), (4, "b".to_string())]);
+    assert_eq!(libs, vec![(2, "x".to_string())]);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn indexed_values_eq() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--inc=a", "--inc", "b"]));
+    let values: Vec<(usize, String)> = args.indexed_values_from_str("--inc").unwrap();
+    assert_eq!(values, vec![(1, "a".to_string()), (2, "b".to_string())]);
+}
+
+pico_args::parse_args! {
+    #[derive(Debug, PartialEq)]
+    struct MacroArgs {
+        /// Verbose output
+        verbose: bool = flag("-v"),
+        /// Sets width
+        width: u32 = value("-w", "--width"),
+        /// Sets height
+        height: u32 = value("--height") default 20,
+        /// Sets an output path
+        output: Option<String> = optional("--output"),
+        /// Include paths
+        include: Vec<String> = values("-I"),
+        /// An input file
+        input: String = free,
+        /// An optional output file
+        /// Uses stdout by default
+        target: Option<String> = optional_free,
+    }
+}
+
+#[test]
+fn parse_args_macro_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-I", "a", "in.txt", "-w", "10", "-I", "b"]));
+    let parsed = MacroArgs::from_args(&mut args).unwrap();
+    assert_eq!(parsed, MacroArgs {
+        verbose: false,
+        width: 10,
+        height: 20,
+        output: None,
+        include: vec!["a".to_string(), "b".to_string()],
+        input: "in.txt".to_string(),
+        target: None,
+    });
+    assert_eq!(args.finish(), to_vec(&[]));
+}
+
+#[test]
+fn parse_args_macro_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "in.txt"]));
+    assert_eq!(MacroArgs::from_args(&mut args).unwrap_err(),
+               Error::MissingOption(Keys::from(["-w", "--width"])));
+}
+
+#[test]
+fn assert_args_macro() {
+    let parsed = assert_args!(MacroArgs, "-v -w 10 -I a -I b in.txt" => {
+        verbose: true,
+        width: 10,
+        include: ["a", "b"],
+        input: "in.txt",
+    });
+    assert_eq!(parsed.target, None);
+
+    let e = assert_args!(MacroArgs, "in.txt" => Err(ErrorKind::MissingOption));
+    assert_eq!(e.key(), Some("--width"));
+}
+
+#[test]
+#[should_panic(expected = "the 'width' field")]
+fn assert_args_macro_mismatch() {
+    assert_args!(MacroArgs, "-w 10 in.txt" => { width: 20 });
+}
+
+#[test]
+fn parse_args_macro_help() {
+    assert_eq!(MacroArgs::HELP, "  -v
+        Verbose output
+  -w, --width <width>
+        Sets width
+  --height <height>
+        Sets height
+  --output <output>
+        Sets an output path
+  -I <include>
+        Include paths
+  <input>
+        An input file
+  <target>
+        An optional output file
+        Uses stdout by default
+");
+}
+
+pico_args::parse_args! {
+    struct LoggingOpts {
+        /// Verbose output
+        verbose: bool = flag("-v", "--verbose"),
+    }
+}
+
+pico_args::parse_args! {
+    struct ServerArgs {
+        /// Sets a port
+        port: u16 = value("--port") default 80,
+        logging: LoggingOpts = flatten,
+    }
+}
+
+struct TlsOpts {
+    cert: Option<String>,
+}
+
+impl FromArguments for TlsOpts {
+    fn from_args(args: &mut Arguments) -> Result<Self, Error> {
+        Ok(TlsOpts { cert: args.opt_value_from_str("--cert")? })
+    }
+}
+
+#[test]
+fn from_arguments_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--verbose", "--port", "8080"]));
+    let server: ServerArgs = args.parse().unwrap();
+    assert_eq!(server.port, 8080);
+    assert!(server.logging.verbose);
+    assert_eq!(ServerArgs::HELP, "  --port <port>\n        Sets a port\n");
+}
+
+#[test]
+fn from_arguments_tuple() {
+    let mut args = Arguments::from_vec(to_vec(&["--cert", "a.pem", "-v"]));
+    let (logging, tls): (LoggingOpts, TlsOpts) = args.parse().unwrap();
+    assert!(logging.verbose);
+    assert_eq!(tls.cert, Some("a.pem".to_string()));
+    assert_eq!(args.finish(), to_vec(&[]));
+}
+
+#[test]
+fn check_conflict_01() {
+    let args = Arguments::from_vec(to_vec(&["--quiet", "-v"]));
+    let e = args.check_conflict("--quiet", ["-v", "--verbose"]).unwrap_err();
+    assert_eq!(e, Error::ConflictingOptions("--quiet", "-v"));
+    assert_eq!(e.to_string(), "the '--quiet' option cannot be used with '-v'");
+    assert!(args.check_conflict("--quiet", "--debug").is_ok());
+}
+
+#[test]
+fn check_requirement_01() {
+    let args = Arguments::from_vec(to_vec(&["--user", "me"]));
+    let e = args.check_requirement("--user", ["-p", "--password"]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::MissingRequiredOption);
+    assert_eq!(e.to_string(), "the '--user' option requires '-p/--password'");
+    assert!(args.check_requirement("--token", "--user").is_ok());
+
+    let args = Arguments::from_vec(to_vec(&["--user", "me", "-p", "secret"]));
+    assert!(args.check_requirement("--user", ["-p", "--password"]).is_ok());
+}
+
+#[test]
+fn check_relations_after_parsing() {
+    let mut args = Arguments::from_vec(to_vec(&["--quiet", "--user", "me", "-v"]));
+    args.set_observer(record_event);
+    assert!(args.contains("--quiet"));
+    assert_eq!(args.value_str("--user").unwrap(), "me");
+    let _ = take_events();
+    assert_eq!(args.check_conflict("--quiet", ["-v", "--verbose"]).unwrap_err(),
+               Error::ConflictingOptions("--quiet", "-v"));
+    assert!(args.contains("-v"));
+    assert_eq!(args.check_conflict("--quiet", ["-v", "--verbose"]).unwrap_err(),
+               Error::ConflictingOptions("--quiet", "-v"));
+    assert!(args.check_requirement("--user", "--password").is_err());
+    assert!(args.check_requirement("-v", "--user").is_ok());
+    assert_eq!(take_events(), vec![
+        "error the '--quiet' option cannot be used with '-v'",
+        "lookup -v",
+        "flag -v 3",
+        "error the '--quiet' option cannot be used with '-v'",
+        "error the '--user' option requires '--password'",
+    ]);
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn check_conflict_eq() {
+    let args = Arguments::from_vec(to_vec(&["--color=never", "--no-color"]));
+    assert_eq!(args.check_conflict("--color", "--no-color").unwrap_err(),
+               Error::ConflictingOptions("--color", "--no-color"));
+}
+
+#[test]
+fn values_bounded_01() {
+    let mut args = Arguments::from_vec(to_vec(&["--input", "a", "--input", "b"]));
+    let values: Vec<String> = args.values_from_str_bounded("--input", 1..=2).unwrap();
+    assert_eq!(values, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[test]
+fn values_bounded_02() {
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    let e = args.values_from_str_bounded::<_, _, String>("--input", 1..).unwrap_err();
+    assert_eq!(e.to_string(), "expected at least 1 '--input', got 0");
+}
+
+#[test]
+fn values_bounded_03() {
+    let mut args = Arguments::from_vec(to_vec(&["-c", "a", "--color", "b", "-c", "c"]));
+    let e = args.values_from_str_bounded::<_, _, String>(["-c", "--color"], ..3).unwrap_err();
+    assert_eq!(e, Error::OccurrencesOutOfRange { key: "--color", count: 3, min: 0, max: Some(2) });
+    assert_eq!(e.to_string(), "expected at most 2 '--color', got 3");
+}
+
+#[test]
+fn toggle_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-x", "file", "+x", "+e"]));
+    assert_eq!(args.toggle("-x"), Some(false));
+    assert_eq!(args.toggle("-x"), None);
+    assert_eq!(args.toggle(["-e", "--errexit"]), Some(false));
+    assert_eq!(args.toggle("-u"), None);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn toggle_02() {
+    let mut args = Arguments::from_vec(to_vec(&["+x", "--xtrace"]));
+    assert_eq!(args.toggle(["-x", "--xtrace"]), Some(true));
+    assert_eq!(args.finish(), to_vec(&[]));
+}
+
+#[test]
+fn values_with_prefix_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-Xmx2g", "-v", "-Xlog=gc=debug", "-X", "-Xa="]));
+    assert_eq!(args.values_with_prefix("-X"), vec![
+        ("mx2g".to_string(), None),
+        ("log".to_string(), Some("gc=debug".to_string())),
+        ("a".to_string(), Some(String::new())),
+    ]);
+    assert!(args.values_with_prefix("-X").is_empty());
+    assert_eq!(args.finish(), to_vec(&["-v", "-X"]));
+}
+
+#[test]
+fn take_scope_01() {
+    let mut args = Arguments::from_vec(to_vec(&[
+        "--ssl-cert", "a.pem", "-v", "--ssl-verify", "--ssl-key", "b.pem", "file",
+    ]));
+    let mut ssl = args.take_scope("--ssl-");
+    assert_eq!(ssl.positions(), &[0, 1, 3, 4, 5]);
+    let cert: String = ssl.value_from_str("--cert").unwrap();
+    assert_eq!(cert, "a.pem");
+    let key: String = ssl.value_from_str("--key").unwrap();
+    assert_eq!(key, "b.pem");
+    assert!(ssl.contains("--verify"));
+    assert_eq!(ssl.finish(), to_vec(&[]));
+    assert_eq!(args.finish(), to_vec(&["-v", "file"]));
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn take_scope_eq() {
+    let mut args = Arguments::from_vec(to_vec(&["--ssl-key=b.pem", "file"]));
+    let mut ssl = args.take_scope("--ssl-");
+    let key: String = ssl.value_from_str("--key").unwrap();
+    assert_eq!(key, "b.pem");
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn extend_from_env_01() {
+    std::env::set_var("PICO_ENV_TEST_OUTPUT_DIR", "out");
+    std::env::set_var("PICO_ENV_TEST_WIDTH", "20");
+    std::env::set_var("PICO_ENV_TEST_VERBOSE", "");
+
+    std::env::set_var("PICO_ENV_TEST_UNRELATED", "1");
+
+    let mut args = Arguments::from_vec(to_vec(&["--width", "10", "file"]));
+    args.extend_from_env("PICO_ENV_TEST_", ["--width", "--output-dir", "--verbose", "--height"]);
+    assert_eq!(args.last_value_from_str::<_, u32>("--width").unwrap(), 10);
+    let dir: String = args.value_from_str("--output-dir").unwrap();
+    assert_eq!(dir, "out");
+    assert!(args.contains("--verbose"));
+    assert_eq!(args.positions(), &[2]);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn extend_from_env_short_key() {
+    std::env::set_var("PICO_ENV_SHORT_TEST_J", "4");
+
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    args.extend_from_env("PICO_ENV_SHORT_TEST_", ["-j"]);
+    assert_eq!(args.value_from_str::<_, u32>("-j").unwrap(), 4);
+}
+
+#[cfg(feature = "dotenv")]
+#[test]
+fn load_dotenv_01() {
+    let path = std::env::temp_dir().join("pico-args-dotenv-01.env");
+    std::fs::write(&path, "# comment\n\nPICO_DOTENV_TEST_WIDTH=20\nexport PICO_DOTENV_TEST_NAME = 'a b'\nPICO_DOTENV_TEST_HEIGHT=5\n").unwrap();
+    std::env::set_var("PICO_DOTENV_TEST_HEIGHT", "7");
+
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    args.load_dotenv(&path).unwrap();
+    args.extend_from_env("PICO_DOTENV_TEST_", ["--width", "--height", "--name"]);
+    let width: u32 = args.value_from_str("--width").unwrap();
+    assert_eq!(width, 20);
+    let height: u32 = args.value_from_str("--height").unwrap();
+    assert_eq!(height, 7);
+    let name: String = args.value_from_str("--name").unwrap();
+    assert_eq!(name, "a b");
+    assert_eq!(args.finish(), to_vec(&[]));
+}
+
+#[cfg(feature = "dotenv")]
+#[test]
+fn load_dotenv_02() {
+    let path = std::env::temp_dir().join("pico-args-dotenv-02.env");
+    std::fs::write(&path, "A=1\nB\n").unwrap();
+
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    let e = args.load_dotenv(&path).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "line 2: expected a KEY=VALUE pair");
+}
+
+#[test]
+fn resolver_01() {
+    std::env::set_var("PICO_RESOLVER_TEST_WIDTH", "30");
+    std::env::set_var("PICO_RESOLVER_TEST_OUTPUT_DIR", "env-out");
+
+    let mut config = std::collections::HashMap::new();
+    config.insert("width".to_string(), "40".to_string());
+    config.insert("height".to_string(), "abc".to_string());
+
+    let mut args = Arguments::from_vec(to_vec(&["--output-dir", "cli-out", "file"]));
+    {
+        let mut resolver = Resolver::new(&mut args, "PICO_RESOLVER_TEST_").with_config(&config);
+        assert_eq!(resolver.get::<String>("--output-dir").unwrap(),
+                   Some(("cli-out".to_string(), Source::CommandLine)));
+        assert_eq!(resolver.get::<u32>("--width").unwrap(), Some((30, Source::Environment)));
+        assert_eq!(resolver.get::<u32>("--height").unwrap_err().to_string(),
+                   "failed to parse '--height abc': invalid digit found in string");
+        assert_eq!(resolver.get::<u32>("--depth").unwrap(), None);
+        assert_eq!(resolver.get_or("--depth", 3u32).unwrap(), (3, Source::Default));
+    }
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+thread_local! {
+    static EVENTS: std::cell::RefCell<Vec<String>> = std::cell::RefCell::new(Vec::new());
+}
+
+fn record_event(event: Event) {
+    let s = match event {
+        Event::Flag { key, position } => format!("flag {} {}", key, position),
+        Event::Value { key, value, position } => format!("value {} {} {}", key, value, position),
+        Event::Free { value, position } => format!("free {} {}", value, position),
+        Event::Error(e) => format!("error {}", e),
+        Event::Lookup(keys) => format!("lookup {}", keys),
+        _ => unreachable!(),
+    };
+    EVENTS.with(|events| events.borrow_mut().push(s));
+}
+
+fn take_events() -> Vec<String> {
+    EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()))
+}
+
+#[test]
+fn observer_01() {
+    let mut args = Arguments::from_vec(to_vec(&["build", "-v", "--width", "10", "file"]));
+    args.set_observer(record_event);
+    let _: Option<String> = args.subcommand().unwrap();
+    assert!(args.contains(["-v", "--verbose"]));
+    let _: u32 = args.value_from_str("--width").unwrap();
+    let _: String = args.free_from_str().unwrap();
+    assert_eq!(take_events(), vec![
+        "free build 0",
+        "lookup -v/--verbose",
+        "flag -v 1",
+        "lookup --width",
+        "value --width 10 2",
+        "free file 4",
+    ]);
+}
+
+#[test]
+fn observer_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "abc"]));
+    args.set_observer(record_event);
+    assert!(args.value_from_str::<_, u32>("--width").is_err());
+    assert!(args.value_from_str::<_, u32>("--height").is_err());
+    assert_eq!(take_events(), vec![
+        "lookup --width",
+        "error failed to parse '--width abc': invalid digit found in string",
+        "lookup --height",
+        "error the '--height' option must be set",
+    ]);
+}
+
+#[test]
+fn observer_values_tuple() {
+    let mut args = Arguments::from_vec(to_vec(&["--size", "10", "20", "--pos", "1"]));
+    args.set_observer(record_event);
+    let _: (u32, u32) = args.values_tuple_from_str("--size").unwrap();
+    assert!(args.opt_values_tuple_from_str::<_, (u32, u32)>("--pos").is_err());
+    assert_eq!(take_events(), vec![
+        "lookup --size",
+        "value --size 10 0",
+        "value --size 20 0",
+        "lookup --pos",
+        "error the '--pos' option doesn't have an associated value",
+    ]);
+    assert_eq!(args.usage("app"), "app --size SIZE [--pos POS]");
+}
+
+#[test]
+fn observer_unknown_subcommand() {
+    let mut args = Arguments::from_vec(to_vec(&["buld"]));
+    args.set_observer(record_event);
+    assert!(args.subcommand_among(&["build"]).is_err());
+    assert_eq!(take_events(), vec!["error unknown subcommand 'buld', did you mean 'build'?"]);
+}
+
+#[test]
+fn observer_values_until() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--exec", "rm", "-f", ";"]));
+    args.set_observer(record_event);
+    assert!(args.values_until("--exec", ";").unwrap().is_some());
+    assert_eq!(take_events(), vec![
+        "lookup --exec",
+        "value --exec rm 1",
+        "value --exec -f 1",
+    ]);
+}
+
+#[test]
+fn observer_try_value() {
+    let mut args = Arguments::from_vec(to_vec(&["--level", "5"]));
+    args.set_observer(record_event);
+    assert!(args.try_opt_value_from_fn("--level", parse_level).is_err());
+    assert_eq!(take_events(), vec![
+        "lookup --level",
+        "error failed to parse '--level 5': level 5 is too high",
+    ]);
+}
+
+#[test]
+fn used_keys_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--width", "10"]));
+    assert!(args.contains(["-v", "--verbose"]));
+    assert!(!args.contains("-q"));
+    let _: Option<u32> = args.opt_value_from_str("--width").unwrap();
+    let _: Vec<u32> = args.values_from_str("--width").unwrap();
+    let _: Option<u32> = args.opt_value_from_str("--height").unwrap();
+    assert_eq!(args.used_keys(), &[
+        Keys::from(["-v", "--verbose"]),
+        Keys::from("-q"),
+        Keys::from("--width"),
+        Keys::from("--height"),
+    ]);
+}
+
+#[test]
+fn help_table_wrap() {
+    let mut table = help::Table::new();
+    table.set_width(40)
+        .section("OPTIONS")
+        .row("--width", "WIDTH", "Sets the width of the output in characters")
+        .row("-q", "", "Quiet")
+        .section("ARGS")
+        .row("<INPUT>", "", "");
+
+    assert_eq!(table.to_string(), "\
+OPTIONS:
+  --width WIDTH  Sets the width of the
+                 output in characters
+  -q             Quiet
+
+ARGS:
+  <INPUT>
+");
+}
+
+#[test]
+fn exit_code() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "abc"]));
+    assert_eq!(args.value_from_str::<_, u32>("--height").unwrap_err().exit_code(), 64);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap_err().exit_code(), 65);
+}
+
+#[test]
+fn parse_duration() {
+    use std::time::Duration;
+    assert_eq!(helpers::parse_duration("250ms").unwrap(), Duration::from_millis(250));
+    assert_eq!(helpers::parse_duration("30").unwrap(), Duration::from_secs(30));
+    assert_eq!(helpers::parse_duration("2m").unwrap(), Duration::from_secs(120));
+    assert_eq!(helpers::parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+    assert_eq!(helpers::parse_duration("1d").unwrap(), Duration::from_secs(86400));
+    assert_eq!(helpers::parse_duration("5w").unwrap_err(),
+               "unknown duration unit 'w', expected ms, s, m, h or d");
+    assert_eq!(helpers::parse_duration("-5s").unwrap_err(), "'-5s' is not a duration");
+    assert_eq!(helpers::parse_duration("99999999999999999999d").unwrap_err(),
+               "duration '99999999999999999999d' is too large");
+}
+
+#[test]
+fn parse_bytes() {
+    assert_eq!(helpers::parse_bytes("512").unwrap(), 512);
+    assert_eq!(helpers::parse_bytes("512B").unwrap(), 512);
+    assert_eq!(helpers::parse_bytes("10k").unwrap(), 10_000);
+    assert_eq!(helpers::parse_bytes("10MB").unwrap(), 10_000_000);
+    assert_eq!(helpers::parse_bytes("10MiB").unwrap(), 10 * 1024 * 1024);
+    assert_eq!(helpers::parse_bytes("1Gi").unwrap(), 1 << 30);
+    assert_eq!(helpers::parse_bytes("1X").unwrap_err(),
+               "unknown size unit in '1X', expected K, M, G, T, Ki, Mi, Gi or Ti");
+    assert_eq!(helpers::parse_bytes("MiB").unwrap_err(), "'MiB' is not a size");
+    assert_eq!(helpers::parse_bytes("99999999Ti").unwrap_err(), "size '99999999Ti' is too large");
+}
+
+#[test]
+fn parse_int() {
+    assert_eq!(helpers::parse_int::<u32>("0o755").unwrap(), 0o755);
+    assert_eq!(helpers::parse_int::<u32>("0xFF_FF").unwrap(), 0xFFFF);
+    assert_eq!(helpers::parse_int::<u8>("0b1010").unwrap(), 10);
+    assert_eq!(helpers::parse_int::<i32>("-0x10").unwrap(), -16);
+    assert_eq!(helpers::parse_int::<u64>("1_000").unwrap(), 1000);
+    assert_eq!(helpers::parse_int::<u8>("0x100").unwrap_err(),
+               "invalid number '0x100': number too large to fit in target type");
+    assert_eq!(helpers::parse_int::<u8>("0x-1").unwrap_err(), "invalid number '0x-1'");
+}
+
+#[test]
+fn parse_socket_addr() {
+    use std::net::SocketAddr;
+    let addr = |s: &str| s.parse::<SocketAddr>().unwrap();
+    assert_eq!(helpers::parse_socket_addr("8080").unwrap(), addr("127.0.0.1:8080"));
+    assert_eq!(helpers::parse_socket_addr("0.0.0.0:80").unwrap(), addr("0.0.0.0:80"));
+    assert_eq!(helpers::parse_socket_addr(":8080").unwrap(), addr("0.0.0.0:8080"));
+    assert_eq!(helpers::parse_socket_addr("localhost:80").unwrap(), addr("127.0.0.1:80"));
+    assert_eq!(helpers::parse_socket_addr("[::1]:80").unwrap(), addr("[::1]:80"));
+    assert_eq!(helpers::parse_socket_addr("1.2.3:80").unwrap_err(),
+               "invalid IP address '1.2.3' in '1.2.3:80'");
+    assert_eq!(helpers::parse_socket_addr("1.2.3.4:http").unwrap_err(),
+               "invalid port 'http' in '1.2.3.4:http'");
+    assert_eq!(helpers::parse_socket_addr("::1:80").unwrap_err(),
+               "IPv6 address must be enclosed in brackets in '::1:80'");
+    assert_eq!(helpers::parse_socket_addr("[::1]").unwrap_err(),
+               "expected '[address]:port' in '[::1]'");
+}
+
+#[test]
+fn parse_range() {
+    assert_eq!(helpers::parse_range::<u32>("10-20").unwrap(), 10..=20);
+    assert_eq!(helpers::parse_range::<u32>("10..20").unwrap(), 10..=20);
+    assert_eq!(helpers::parse_range::<u32>("10..=20").unwrap(), 10..=20);
+    assert_eq!(helpers::parse_range::<i32>("-5..=-3").unwrap(), -5..=-3);
+    assert_eq!(helpers::parse_range::<u32>("5").unwrap(), 5..=5);
+    assert_eq!(helpers::parse_range::<i32>("-5--3").unwrap(), -5..=-3);
+    assert_eq!(helpers::parse_range::<i32>("-5..3").unwrap(), -5..=3);
+    assert_eq!(helpers::parse_range::<u32>("20-10").unwrap_err(),
+               "the start of the range '20-10' is greater than the end");
+    assert_eq!(helpers::parse_range::<u32>("1-x").unwrap_err(),
+               "invalid number 'x' in '1-x': invalid digit found in string");
+}
+
+#[test]
+fn parse_list() {
+    assert_eq!(helpers::parse_list::<u32>("1, 2,3").unwrap(), vec![1, 2, 3]);
+    assert_eq!(helpers::parse_list::<u32>("").unwrap(), Vec::<u32>::new());
+    assert_eq!(helpers::parse_list::<u32>("1,x,3").unwrap_err(),
+               "invalid list element #2 'x': invalid digit found in string");
+}
+
+#[test]
+fn color_choice() {
+    use helpers::ColorChoice;
+    assert_eq!("ansi".parse::<ColorChoice>().unwrap(), ColorChoice::Always);
+    assert_eq!("never".parse::<ColorChoice>().unwrap(), ColorChoice::Never);
+    assert_eq!("yes".parse::<ColorChoice>().unwrap_err(),
+               "invalid color choice 'yes', expected auto, always or never");
+    assert!(ColorChoice::Always.should_colorize(false));
+    assert!(!ColorChoice::Auto.should_colorize(false));
+}
+
+#[test]
+fn verbosity() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--quiet", "file", "--verbose", "-v"]));
+    let verbosity = helpers::Verbosity::from_args(&mut args);
+    assert_eq!(verbosity.verbose(), 3);
+    assert_eq!(verbosity.quiet(), 1);
+    assert_eq!(verbosity.level(), 2);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn input() {
+    use helpers::Input;
+    let mut args = Arguments::from_vec(to_vec(&["-i", "-", "in.txt"]));
+    assert_eq!(args.value_from_str::<_, Input>("-i").unwrap(), Input::Stdin);
+    assert_eq!(args.free_from_str::<Input>().unwrap(), Input::Path("in.txt".into()));
+}
+
+#[test]
+fn output() {
+    use helpers::Output;
+    let mut args = Arguments::from_vec(to_vec(&["-o", "-", "out.txt"]));
+    assert_eq!(args.value_from_str::<_, Output>("-o").unwrap(), Output::Stdout);
+    assert_eq!(args.free_from_str::<Output>().unwrap(), Output::Path("out.txt".into()));
+}
+
+#[test]
+fn parse_percent() {
+    assert_eq!(helpers::parse_percent("50%").unwrap(), 0.5);
+    assert_eq!(helpers::parse_percent("50").unwrap(), 0.5);
+    assert_eq!(helpers::parse_percent("0.5").unwrap(), 0.5);
+    assert_eq!(helpers::parse_percent("0.25").unwrap(), 0.25);
+    assert_eq!(helpers::parse_percent("1").unwrap(), 1.0);
+    assert_eq!(helpers::parse_percent("1.5").unwrap(), 0.015);
+    assert_eq!(helpers::parse_percent("0.5%").unwrap(), 0.005);
+    assert_eq!(helpers::parse_percent("-5").unwrap(), 0.0);
+    assert_eq!(helpers::parse_percent("1/2").unwrap_err(), "invalid percentage '1/2'");
+    assert_eq!(helpers::parse_percent("150%").unwrap(), 1.0);
+    assert_eq!(helpers::parse_percent("-5%").unwrap(), 0.0);
+    assert_eq!(helpers::parse_percent("half").unwrap_err(), "invalid percentage 'half'");
+    assert_eq!(helpers::parse_percent("NaN").unwrap_err(), "invalid percentage 'NaN'");
+
+    let mut args = Arguments::from_vec(to_vec(&["--quality", "5%"]));
+    let quality = args.value_from_fn("--quality", helpers::parse_percent_in(0.1, 0.9)).unwrap();
+    assert_eq!(quality, 0.1);
+}
+
+#[test]
+fn parse_key_val() {
+    assert_eq!(helpers::parse_key_val::<String, String>("a=b=c").unwrap(),
+               ("a".to_string(), "b=c".to_string()));
+    assert_eq!(helpers::parse_key_val::<String, u32>("a").unwrap_err(),
+               "expected a KEY=VALUE pair, got 'a'");
+    assert_eq!(helpers::parse_key_val::<String, u32>("a=b").unwrap_err(),
+               "invalid value 'b': invalid digit found in string");
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_path_expand_tilde() {
+    use std::path::Path;
+
+    let root = std::env::temp_dir().join("pico-args-tilde");
+    std::fs::create_dir_all(root.join("usr")).unwrap();
+    std::env::set_var("HOME", root.join("user"));
+    let parse = |s: &str| helpers::parse_path_expand_tilde(s.as_ref()).unwrap();
+    assert_eq!(parse("~"), root.join("user"));
+    assert_eq!(parse("~/a/b"), root.join("user/a/b"));
+    // `~user` is never resolved, even when a sibling directory exists.
+    assert_eq!(parse("~usr/a"), Path::new("~usr/a"));
+    assert_eq!(parse("~user/a"), Path::new("~user/a"));
+    assert_eq!(parse("~~/a"), Path::new("~~/a"));
+    assert_eq!(parse("a/~/b"), Path::new("a/~/b"));
+}
+
+#[test]
+fn resolve_path() {
+    let root = std::env::temp_dir().join("pico-args-resolve");
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    let root = std::fs::canonicalize(root).unwrap();
+    let parse = helpers::resolve_path(&root);
+    assert_eq!(parse("sub".as_ref()).unwrap(), root.join("sub"));
+    assert_eq!(parse("sub/..".as_ref()).unwrap(), root);
+    assert_eq!(parse(root.join("sub").as_os_str()).unwrap(), root.join("sub"));
+    assert!(parse("missing".as_ref()).unwrap_err().starts_with("cannot resolve"));
+}
+
+#[test]
+fn value_from_str_in_range() {
+    let mut args = Arguments::from_vec(to_vec(&["--jobs", "8", "--level", "0"]));
+    assert_eq!(args.value_from_str_in_range::<_, _, u32>("--jobs", 1..=64).unwrap(), 8);
+    assert_eq!(args.value_from_str_in_range::<_, _, i32>("--level", 1..).unwrap_err().to_string(),
+               "failed to parse '--level 0': expected a value in 1..");
+    assert_eq!(args.opt_value_from_str_in_range::<_, _, u32>("--depth", ..10).unwrap(), None);
+}
+
+#[test]
+fn strict_values() {
+    let mut args = Arguments::from_vec(to_vec(&["--output", "--verbose", "--offset", "-5", "-i", "-"]));
+    args.set_strict_values(true);
+    let e = args.value_from_str::<_, String>("--output").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::FlagLikeValue);
+    assert_eq!(e.to_string(), "the '--output' option expects a value, got '--verbose'");
+    assert_eq!(args.value_from_str::<_, i32>("--offset").unwrap(), -5);
+    assert_eq!(args.value_from_str::<_, String>("-i").unwrap(), "-");
+}
+
+#[test]
+fn strict_values_disabled() {
+    let mut args = Arguments::from_vec(to_vec(&["--output", "--verbose"]));
+    assert_eq!(args.value_from_str::<_, String>("--output").unwrap(), "--verbose");
+}
+
+#[test]
+fn try_finish() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "extra"]));
+    assert!(args.contains("-v"));
+    assert_eq!(args.clone().try_finish(), Err(to_vec(&["extra"])));
+    let _: String = args.free_from_str().unwrap();
+    assert_eq!(args.try_finish(), Ok(()));
+}
+
+#[test]
+fn iter_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "https://example.com", "file"]));
+    assert!(args.contains("-v"));
+    let url = Arg::from("https://example.com");
+    assert_eq!(args.iter().next(), Some(&*url));
+    assert_eq!(args.iter().count(), 2);
+    assert_eq!(args.into_iter().collect::<Vec<_>>(), to_vec(&["https://example.com", "file"]));
+}
+
+#[test]
+fn push_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v"]));
+    args.push("--width");
+    args.extend(vec!["10", "file"]);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+    assert_eq!(args.consumed(), &[1, 2]);
+    assert!(args.contains("-v"));
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn push_front_01() {
+    let mut args = Arguments::from_vec(to_vec(&["build", "file"]));
+    let name: String = args.free_from_str().unwrap();
+    assert_eq!(name, "build");
+    args.push_front(name);
+    assert_eq!(args.subcommand().unwrap(), Some("build".to_string()));
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn len_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "file"]));
+    assert_eq!(args.len(), 2);
+    assert!(args.contains("-v"));
+    assert_eq!(args.remaining_os(), &to_vec(&["file"])[..]);
+    let _: String = args.free_from_str().unwrap();
+    assert!(args.is_empty());
+}
+
+#[test]
+fn split_at_dash_dash_01() {
+    let args = Arguments::from_vec(to_vec(&["-v", "file", "--", "--width", "10"]));
+    let (mut args, mut forwarded) = args.split_at_dash_dash();
+    assert!(args.contains("-v"));
+    assert_eq!(args.finish(), to_vec(&["file"]));
+    assert_eq!(forwarded.positions(), &[3, 4]);
+    assert_eq!(forwarded.value_from_str::<_, u32>("--width").unwrap(), 10);
+    assert!(forwarded.is_empty());
+}
+
+#[test]
+fn split_at_dash_dash_02() {
+    let args = Arguments::from_vec(to_vec(&["-v"]));
+    let (args, forwarded) = args.split_at_dash_dash();
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+    assert!(forwarded.is_empty());
+}
+
+#[test]
+fn take_subcommand_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "build", "--release", "file"]));
+    let (name, mut sub_args) = args.take_subcommand().unwrap().unwrap();
+    assert_eq!(name, "build");
+    assert_eq!(sub_args.positions(), &[2, 3]);
+    assert!(sub_args.contains("--release"));
+    assert_eq!(sub_args.finish(), to_vec(&["file"]));
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+}
+
+#[test]
+fn take_subcommand_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--", "build"]));
+    assert_eq!(args.take_subcommand().unwrap().map(|(name, _)| name), None);
+    assert_eq!(args.finish(), to_vec(&["-v", "--", "build"]));
+}
+
+#[test]
+fn numeric_option_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-n", "-5", "-20", "file"]));
+    assert!(args.contains("-n"));
+    assert_eq!(args.numeric_option().unwrap(), Some(5));
+    assert_eq!(args.numeric_option().unwrap(), Some(20));
+    assert_eq!(args.numeric_option().unwrap(), None);
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn numeric_option_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-99999999999999999999"]));
+    assert_eq!(args.numeric_option().unwrap_err().to_string(),
+               "failed to parse '-99999999999999999999': number too large to fit in target type");
+}
+
+#[test]
+fn value_from_str_then_rest_01() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "-e", "script", "-x", "--", "a"]));
+    let (script, rest): (String, _) = args.value_from_str_then_rest("-e").unwrap();
+    assert_eq!(script, "script");
+    assert_eq!(rest, to_vec(&["-x", "--", "a"]));
+    assert_eq!(args.consumed(), &[1, 2, 3, 4, 5]);
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+}
+
+#[test]
+fn value_from_str_then_rest_02() {
+    let mut args = Arguments::from_vec(to_vec(&["-e", "abc", "-x"]));
+    assert!(args.value_from_str_then_rest::<_, u32>("-e").is_err());
+    assert_eq!(args.finish(), to_vec(&["-e", "abc", "-x"]));
+}
+
+#[test]
+fn values_until_01() {
+    let mut args = Arguments::from_vec(to_vec(&[
+        "--exec", "rm", "{}", ";", "-v", "--exec", "echo", "-n", "+",
+    ]));
+    assert_eq!(args.values_until("--exec", ";").unwrap(), Some(to_vec(&["rm", "{}"])));
+    assert_eq!(args.values_until("--exec", "+").unwrap(), Some(to_vec(&["echo", "-n"])));
+    assert_eq!(args.values_until("--exec", ";").unwrap(), None);
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+}
+
+#[test]
+fn values_until_02() {
+    let mut args = Arguments::from_vec(to_vec(&["--exec", "rm"]));
+    assert_eq!(args.values_until("--exec", ";").unwrap_err().to_string(),
+               "the '--exec' option must be terminated by ';'");
+}
+
+#[cfg(all(windows, feature = "wild"))]
+#[test]
+fn wildcards() {
+    let dir = std::env::temp_dir().join("pico-args-wildcards");
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in &["b.txt", "a.txt", "c.md", "\u{e4}.md"] {
+        std::fs::write(dir.join(name), "").unwrap();
+    }
+
+    let pattern = format!("{}\\*.TXT", dir.display());
+    let mut args = Arguments::from_vec(to_vec(&[&pattern, "-v"]));
+    let first: String = args.free_from_str().unwrap();
+    assert!(first.ends_with("\\a.txt"));
+    let second: String = args.opt_free_skip_flags_from_str().unwrap().unwrap();
+    assert!(second.ends_with("\\b.txt"));
+    assert_eq!(args.finish(), to_vec(&["-v"]));
+
+    // Remaining patterns are expanded as well. `?` matches a non-ASCII character.
+    let txt = format!("{}\\*.txt", dir.display());
+    let md = format!("{}\\?.md", dir.display());
+    let args = Arguments::from_vec(to_vec(&["-v", &txt, &md]));
+    let rest: Vec<String> = args.finish().iter()
+        .map(|s| std::path::Path::new(s).to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(rest.len(), 5);
+    assert_eq!(rest[0], "-v");
+    assert!(rest[2].ends_with("\\b.txt"));
+    assert!(rest[3].ends_with("\\c.md"));
+    assert!(rest[4].ends_with("\\\u{e4}.md"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn from_source() {
+    let mut args = Arguments::from_source(vec!["-v", "file"]);
+    assert!(args.contains("-v"));
+    assert_eq!(args.finish(), to_vec(&["file"]));
+}
+
+#[test]
+fn from_nul_separated() {
+    let mut args = Arguments::from_nul_separated(b"app\0-v\0\0file");
+    assert!(args.contains("-v"));
+    assert_eq!(args.finish(), to_vec(&["", "file"]));
+
+    assert!(Arguments::from_nul_separated(b"").is_empty());
+    assert!(Arguments::from_nul_separated(b"app\0").is_empty());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn stats() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--width", "10", "file"]));
+    assert_eq!(args.stats(), Stats::default());
+    assert!(args.contains("-v"));
+    assert!(!args.contains("-q"));
+    let width: u32 = args.value_from_str("--width").unwrap();
+    assert_eq!(width, 10);
+
+    let stats = args.stats();
+    assert_eq!(stats.removed, 3);
+    assert!(stats.scans >= 3);
+    assert_eq!(stats.allocations, 0);
+    assert_eq!(args.clone().stats(), stats);
+}
+
+#[test]
+fn error_code() {
+    assert_eq!(Error::NonUtf8Argument.kind().code(), 1);
+    assert_eq!(Error::MissingOption(Keys::from("--width")).kind().code(), 3);
+}
+
+#[cfg(feature = "min-size-errors")]
+#[test]
+fn min_size_errors() {
+    assert_eq!(Error::MissingArgument.to_string(), "E2");
+    assert_eq!(Error::MissingOption(Keys::from(["-w", "--width"])).to_string(), "E3 --width");
+
+    let mut args = Arguments::from_vec(to_vec(&["--width", "a"]));
+    let e = args.value_from_str::<_, u32>("--width").unwrap_err();
+    assert_eq!(e.to_string(), "E6 --width");
+}
+
+#[test]
+fn probe_value_from_fn() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--jobs", "auto", "--width"]));
+    let e = args.probe_value_from_fn("--jobs", |s| s.parse::<u32>()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::OptionParsingFailed);
+    assert_eq!(e.key(), Some("--jobs"));
+    assert_eq!(e.position(), Some(2));
+
+    let e = args.probe_value_from_fn("--width", |s| s.parse::<u32>()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::OptionWithoutAValue);
+    assert_eq!(e.position(), Some(3));
+
+    assert_eq!(args.probe_value_from_fn("--height", |s| s.parse::<u32>()), Ok(None));
+    let jobs = args.probe_value_from_fn("--jobs", |s| Ok::<_, ()>(s.to_string())).unwrap();
+    assert_eq!(jobs, Some("auto".to_string()));
+    assert_eq!(args.finish(), to_vec(&["-v", "--width"]));
+}
+
+#[test]
+fn error_display_with() {
+    let mut args = Arguments::from_vec(to_vec(&[]));
+    let e = args.value_from_str::<_, u32>("--width").unwrap_err();
+    let prefix = "Fehler";
+    let german = |e: &Error, f: &mut std::fmt::Formatter| match e.kind() {
+        ErrorKind::MissingOption => write!(f, "{}: '{}' fehlt", prefix, e.key().unwrap()),
+        _ => std::fmt::Display::fmt(e, f),
+    };
+    assert_eq!(e.display_with(german).to_string(), "Fehler: '--width' fehlt");
+    assert_eq!(e.to_string(), "the '--width' option must be set");
+    assert_eq!(Error::MissingArgument.display_with(german).to_string(),
+               "free-standing argument is missing");
+}
+
+#[test]
+fn error_is_send_sync() {
+    fn check<T: Send + Sync + 'static>() {}
+    check::<Error>();
+    check::<ProbeError>();
+    check::<Arguments>();
+
+    fn io() -> std::io::Result<u32> {
+        let mut args = Arguments::from_vec(to_vec(&[]));
+        Ok(args.value_from_str("--width")?)
+    }
+
+    let e = io().unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(e.to_string(), "the '--width' option must be set");
+
+    let e: Box<dyn std::error::Error + Send + Sync> = Error::MissingArgument.into();
+    assert!(e.downcast_ref::<Error>().is_some());
+}
+
+#[test]
+fn testing_permute() {
+    use pico_args::testing::{permute, Rng};
+
+    let units: &[&[&str]] = &[&["-v"], &["a"], &["--width", "10"], &["b"], &["-"]];
+    let mut rng = Rng::new(7);
+    for _ in 0..32 {
+        let args = permute(units, &mut rng);
+        assert_eq!(args.len(), 6);
+        let free: Vec<_> = args.iter().filter(|s| ["a", "b", "-"].contains(&s.as_str())).collect();
+        assert_eq!(free, ["a", "b", "-"]);
+        let idx = args.iter().position(|s| s == "--width").unwrap();
+        assert_eq!(args[idx + 1], "10");
+    }
+
+    assert_eq!(permute(units, &mut Rng::new(1)), permute(units, &mut Rng::new(1)));
+}
+
+#[test]
+fn testing_order_insensitive() {
+    fn parse(mut args: Arguments) -> Result<(bool, Option<u32>, Vec<String>), Error> {
+        let verbose = args.contains("-v");
+        let width = args.opt_value_from_str("--width")?;
+        let mut free = Vec::new();
+        while let Some(value) = args.opt_free_from_str()? {
+            free.push(value);
+        }
+        Ok((verbose, width, free))
+    }
+
+    testing::assert_order_insensitive(&[&["-v"], &["a"], &["--width", "10"], &["b"]], parse);
+}
+
+#[test]
+#[should_panic(expected = "produced")]
+fn testing_order_sensitive() {
+    // The first free-standing argument is parsed before options.
+    fn parse(mut args: Arguments) -> Option<String> {
+        args.opt_free_from_str().unwrap()
+    }
+
+    testing::assert_order_insensitive(&[&["--width", "10"], &["a"]], parse);
+}
+
+#[test]
+fn error_debug() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "a"]));
+    let e = args.value_from_str::<_, u32>("--width").unwrap_err();
+    assert_eq!(format!("{:?}", e), "Error { kind: OptionParsingFailed, key: Some(\"--width\"), \
+        value: Some(\"a\"), cause: Some(\"invalid digit found in string\"), \
+        message: \"failed to parse '--width a': invalid digit found in string\" }");
+
+    assert_eq!(format!("{:?}", Error::MissingArgument), "Error { kind: MissingArgument, key: None, \
+        value: None, cause: None, message: \"free-standing argument is missing\" }");
+}
+
+#[test]
+fn usage() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "--width", "10"]));
+    assert!(args.contains(["-v", "--verbose"]));
+    let _: Option<u32> = args.opt_value_from_str("--width").unwrap();
+    let _: Vec<String> = args.values_from_str("-I").unwrap();
+    assert!(args.value_from_str::<_, String>("--max-depth").is_err());
+    let _: Option<u32> = args.opt_value_from_str("--max-depth").unwrap();
+    assert!(args.free_from_str::<String>().is_err());
+    let _: Option<String> = args.opt_free_from_str().unwrap();
+    let _: Option<String> = args.opt_free_from_str().unwrap();
+    assert_eq!(args.usage("app"),
+               "app [--verbose] [--width WIDTH] [-I I]... --max-depth MAX_DEPTH ARG [ARG]...");
+
+    assert_eq!(Arguments::from_vec(to_vec(&[])).usage("app"), "app");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn separator_policy() {
+    let mut args = Arguments::from_vec(to_vec(&["--width=10", "--height", "20"]));
+    args.set_separator_policy(SeparatorPolicy::SpaceOnly);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap_err(),
+               Error::OptionWithoutAValue("--width"));
+    assert_eq!(args.value_from_str::<_, u32>("--height").unwrap(), 20);
+
+    let mut args = Arguments::from_vec(to_vec(&["--width=10", "--height", "20"]));
+    args.set_separator_policy(SeparatorPolicy::EqOnly);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+    assert_eq!(args.value_from_str::<_, u32>("--height").unwrap_err(),
+               Error::OptionWithoutAValue("--height"));
+
+    let mut args = Arguments::from_vec(to_vec(&["--width=10", "--height", "20"]));
+    args.set_separator_policy(SeparatorPolicy::Both);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+    assert_eq!(args.value_from_str::<_, u32>("--height").unwrap(), 20);
+}
+
+#[test]
+fn quote_mode_all() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "'10'", "--name", "\"a \\\"b\\\"\"",
+                                                "--path", "'unmatched", "--raw", "'x'"]));
+    args.set_quote_mode(QuoteMode::All);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+    assert_eq!(args.value_str("--name").unwrap(), "a \"b\"");
+    assert_eq!(args.value_str("--path").unwrap(), "'unmatched");
+    assert_eq!(args.finish(), to_vec(&["--raw", "'x'"]));
+}
+
+#[test]
+fn quote_mode_default_keeps_separate_values() {
+    let mut args = Arguments::from_vec(to_vec(&["--width", "'10'"]));
+    assert_eq!(args.value_str("--width").unwrap(), "'10'");
+}
+
+#[test]
+fn expect_free() {
+    let mut args = Arguments::from_vec(to_vec(&["-v", "a", "b"]));
+    assert!(args.contains("-v"));
+    assert_eq!(args.expect_free(2..=2).unwrap(), 2);
+    assert_eq!(args.expect_free(1..).unwrap(), 2);
+    assert_eq!(args.expect_free(..=1).unwrap_err().to_string(), "expected at most 1 argument, got 2");
+    assert_eq!(args.expect_free(3..5).unwrap_err().to_string(), "expected 3 to 4 arguments, got 2");
+    assert_eq!(args.expect_free(3..).unwrap_err().kind(), ErrorKind::ArgumentCountOutOfRange);
+    assert_eq!(args.free_from_str::<String>().unwrap(), "a");
+}
+
+#[test]
+fn expect_free_named() {
+    let args = Arguments::from_vec(to_vec(&["a", "b", "c"]));
+    assert_eq!(args.expect_free_named(&["SRC", "DST"]).unwrap_err().to_string(),
+               "expected 2 arguments (SRC, DST), got 3");
+    assert_eq!(args.expect_free_named(&["FILE"]).unwrap_err().to_string(),
+               "expected 1 argument (FILE), got 3");
+    assert!(args.expect_free_named(&["A", "B", "C"]).is_ok());
+}
+
+#[test]
+fn free_from_str_named() {
+    let mut args = Arguments::from_vec(to_vec(&["in.txt", "x"]));
+    assert_eq!(args.free_from_str_named::<String>("FILE").unwrap(), "in.txt");
+    let e = args.free_from_str_named::<u32>("COUNT").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::Utf8ArgumentParsingFailed);
+    assert_eq!(e.value(), Some("x"));
+    assert_eq!(e.to_string(), "invalid value for <COUNT>: invalid digit found in string");
+    let e = args.free_from_str_named::<String>("OUTPUT").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::MissingArgument);
+    assert_eq!(e.to_string(), "the <OUTPUT> argument is missing");
+    assert_eq!(args.usage("app"), "app FILE COUNT OUTPUT");
+}
+
+#[test]
+fn subcommand_path() {
+    let mut args = Arguments::from_vec(to_vec(&["remote", "add", "origin"]));
+    assert_eq!(args.subcommand_path(2).unwrap(), ["remote", "add"]);
+    assert_eq!(args.finish(), to_vec(&["origin"]));
+
+    let mut args = Arguments::from_vec(to_vec(&["remote", "-v", "show"]));
+    assert_eq!(args.subcommand_path(3).unwrap(), ["remote"]);
+    assert_eq!(args.finish(), to_vec(&["-v", "show"]));
+
+    let mut args = Arguments::from_vec(to_vec(&["remote"]));
+    assert!(args.subcommand_path(0).unwrap().is_empty());
+}
+
+#[test]
+fn env_interpolation() {
+    std::env::set_var("PICO_ARGS_TEST_DIR", "/tmp");
+    let mut args = Arguments::from_vec(to_vec(&["--out", "${PICO_ARGS_TEST_DIR}/a-$${x}",
+                                                "--name", "$PICO_ARGS_TEST_DIR", "--raw", "${x}"]));
+    args.set_env_interpolation(true);
+    assert_eq!(args.value_str("--out").unwrap(), "/tmp/a-${x}");
+    assert_eq!(args.value_str("--name").unwrap(), "$PICO_ARGS_TEST_DIR");
+    assert_eq!(args.finish(), to_vec(&["--raw", "${x}"]));
+}
+
+#[test]
+fn env_interpolation_undefined() {
+    let mut args = Arguments::from_vec(to_vec(&["--out", "${PICO_ARGS_TEST_UNDEFINED}"]));
+    args.set_env_interpolation(true);
+    let e = args.value_str("--out").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UndefinedVariable);
+    assert_eq!(e.value(), Some("PICO_ARGS_TEST_UNDEFINED"));
+    assert_eq!(e.to_string(),
+               "the '--out' option refers to an undefined variable 'PICO_ARGS_TEST_UNDEFINED'");
+}
+
+#[cfg(feature = "eq-separator")]
+#[test]
+fn env_interpolation_eq_separator() {
+    std::env::set_var("PICO_ARGS_TEST_WIDTH", "10");
+    let mut args = Arguments::from_vec(to_vec(&["--width='${PICO_ARGS_TEST_WIDTH}'"]));
+    args.set_env_interpolation(true);
+    assert_eq!(args.value_from_str::<_, u32>("--width").unwrap(), 10);
+}